@@ -0,0 +1,454 @@
+//! The table mapping every bloom tuner action to a key, loaded from a RON config file of defaults
+//! tracked in the repository, with the user's rebinds loaded on top from a file ignored by git.
+
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs, io,
+    path::Path,
+};
+
+use crate::user_settings::SettingsError;
+
+pub const DEFAULT_BINDINGS_PATH: &str = "assets/config/bindings.ron";
+pub const DEFAULT_USER_BINDINGS_PATH: &str = "assets/config/user/bindings.ron";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum TunerAction {
+    ToggleBloom,
+    IntensityUp,
+    IntensityDown,
+    LowFrequencyBoostUp,
+    LowFrequencyBoostDown,
+    LowFrequencyBoostCurvatureUp,
+    LowFrequencyBoostCurvatureDown,
+    HighPassFrequencyUp,
+    HighPassFrequencyDown,
+    CompositeModeAdditive,
+    CompositeModeEnergyConserving,
+    ThresholdUp,
+    ThresholdDown,
+    ThresholdSoftnessUp,
+    ThresholdSoftnessDown,
+    FovUp,
+    FovDown,
+    ToggleProjection,
+    CycleTonemapping,
+    CycleBounceMotion,
+    ToggleDiagnostics,
+    ToggleGround,
+    ToggleSkybox,
+    AmbientUp,
+    AmbientDown,
+    FlySpeedUp,
+    FlySpeedDown,
+    SensitivityUp,
+    SensitivityDown,
+    SaveUserSettings,
+    OpenBindings,
+    TogglePanel,
+    ToggleComparison,
+    SwitchComparisonSide,
+    CopyToOtherSide,
+    PreviousMaterialField,
+    NextMaterialField,
+    MaterialValueUp,
+    MaterialValueDown,
+    SavePreset,
+    SaveNewPreset,
+    NextPreset,
+    LoadPreset,
+    /// Triggered together with Ctrl.
+    Undo,
+    /// Triggered together with Ctrl.
+    Redo,
+}
+
+impl TunerAction {
+    /// Whether the action is triggered together with Ctrl.
+    pub fn is_shortcut(self) -> bool {
+        matches!(self, Self::Undo | Self::Redo)
+    }
+
+    pub const ALL: [Self; 45] = [
+        Self::ToggleBloom,
+        Self::IntensityUp,
+        Self::IntensityDown,
+        Self::LowFrequencyBoostUp,
+        Self::LowFrequencyBoostDown,
+        Self::LowFrequencyBoostCurvatureUp,
+        Self::LowFrequencyBoostCurvatureDown,
+        Self::HighPassFrequencyUp,
+        Self::HighPassFrequencyDown,
+        Self::CompositeModeAdditive,
+        Self::CompositeModeEnergyConserving,
+        Self::ThresholdUp,
+        Self::ThresholdDown,
+        Self::ThresholdSoftnessUp,
+        Self::ThresholdSoftnessDown,
+        Self::FovUp,
+        Self::FovDown,
+        Self::ToggleProjection,
+        Self::CycleTonemapping,
+        Self::CycleBounceMotion,
+        Self::ToggleDiagnostics,
+        Self::ToggleGround,
+        Self::ToggleSkybox,
+        Self::AmbientUp,
+        Self::AmbientDown,
+        Self::FlySpeedUp,
+        Self::FlySpeedDown,
+        Self::SensitivityUp,
+        Self::SensitivityDown,
+        Self::SaveUserSettings,
+        Self::OpenBindings,
+        Self::TogglePanel,
+        Self::ToggleComparison,
+        Self::SwitchComparisonSide,
+        Self::CopyToOtherSide,
+        Self::PreviousMaterialField,
+        Self::NextMaterialField,
+        Self::MaterialValueUp,
+        Self::MaterialValueDown,
+        Self::SavePreset,
+        Self::SaveNewPreset,
+        Self::NextPreset,
+        Self::LoadPreset,
+        Self::Undo,
+        Self::Redo,
+    ];
+
+    pub fn default_key(self) -> KeyCode {
+        match self {
+            Self::ToggleBloom => KeyCode::Space,
+            Self::IntensityUp => KeyCode::KeyP,
+            Self::IntensityDown => KeyCode::Semicolon,
+            Self::LowFrequencyBoostUp => KeyCode::KeyO,
+            Self::LowFrequencyBoostDown => KeyCode::KeyL,
+            Self::LowFrequencyBoostCurvatureUp => KeyCode::KeyI,
+            Self::LowFrequencyBoostCurvatureDown => KeyCode::KeyK,
+            Self::HighPassFrequencyUp => KeyCode::KeyU,
+            Self::HighPassFrequencyDown => KeyCode::KeyJ,
+            Self::CompositeModeAdditive => KeyCode::KeyY,
+            Self::CompositeModeEnergyConserving => KeyCode::KeyH,
+            Self::ThresholdUp => KeyCode::KeyT,
+            Self::ThresholdDown => KeyCode::KeyG,
+            Self::ThresholdSoftnessUp => KeyCode::KeyR,
+            Self::ThresholdSoftnessDown => KeyCode::KeyF,
+            Self::FovUp => KeyCode::BracketLeft,
+            Self::FovDown => KeyCode::BracketRight,
+            Self::ToggleProjection => KeyCode::KeyV,
+            Self::CycleTonemapping => KeyCode::KeyM,
+            Self::CycleBounceMotion => KeyCode::KeyN,
+            Self::ToggleDiagnostics => KeyCode::F3,
+            Self::ToggleGround => KeyCode::Digit1,
+            Self::ToggleSkybox => KeyCode::Digit2,
+            Self::AmbientUp => KeyCode::Digit4,
+            Self::AmbientDown => KeyCode::Digit3,
+            Self::FlySpeedUp => KeyCode::PageUp,
+            Self::FlySpeedDown => KeyCode::PageDown,
+            Self::SensitivityUp => KeyCode::Home,
+            Self::SensitivityDown => KeyCode::End,
+            Self::SaveUserSettings => KeyCode::F8,
+            Self::OpenBindings => KeyCode::F1,
+            Self::TogglePanel => KeyCode::F2,
+            Self::ToggleComparison => KeyCode::KeyC,
+            Self::SwitchComparisonSide => KeyCode::KeyX,
+            Self::CopyToOtherSide => KeyCode::KeyB,
+            Self::PreviousMaterialField => KeyCode::Comma,
+            Self::NextMaterialField => KeyCode::Period,
+            Self::MaterialValueUp => KeyCode::Equal,
+            Self::MaterialValueDown => KeyCode::Minus,
+            Self::SavePreset => KeyCode::F5,
+            Self::SaveNewPreset => KeyCode::F7,
+            Self::NextPreset => KeyCode::F6,
+            Self::LoadPreset => KeyCode::F9,
+            Self::Undo => KeyCode::KeyZ,
+            Self::Redo => KeyCode::KeyY,
+        }
+    }
+}
+
+/// Maps each [`TunerAction`] to the key which triggers it.
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+pub struct TunerBindings(pub BTreeMap<TunerAction, KeyCode>);
+
+impl Default for TunerBindings {
+    fn default() -> Self {
+        Self(
+            TunerAction::ALL
+                .into_iter()
+                .map(|action| (action, action.default_key()))
+                .collect(),
+        )
+    }
+}
+
+impl TunerBindings {
+    /// Reads the bindings file at `path`, falling back to the default key for any action the file
+    /// does not bind, or to the defaults entirely if the file is missing or invalid.
+    pub fn load_or_default(path: &Path) -> Self {
+        let mut bindings = Self::default();
+
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) => {
+                info!(
+                    "Using default key bindings, could not read {}: {err}",
+                    path.display()
+                );
+                return bindings;
+            }
+        };
+
+        match ron::from_str::<Self>(&contents) {
+            Ok(loaded) => bindings.0.extend(loaded.0),
+            Err(err) => error!(
+                "Using default key bindings, could not parse {}: {err}",
+                path.display()
+            ),
+        }
+
+        bindings
+    }
+
+    /// Reads the default bindings from `path`, then the user's rebinds from `overrides_path` on top
+    /// of them. A missing overrides file just means nothing has been rebound.
+    pub fn load_with_overrides(path: &Path, overrides_path: &Path) -> Self {
+        let mut bindings = Self::load_or_default(path);
+
+        let contents = match fs::read_to_string(overrides_path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return bindings,
+            Err(err) => {
+                error!(
+                    "Ignoring rebound keys, could not read {}: {err}",
+                    overrides_path.display()
+                );
+                return bindings;
+            }
+        };
+
+        match ron::from_str::<Self>(&contents) {
+            Ok(overrides) => bindings.0.extend(overrides.0),
+            Err(err) => error!(
+                "Ignoring rebound keys, could not parse {}: {err}",
+                overrides_path.display()
+            ),
+        }
+
+        bindings
+    }
+
+    /// The bindings which differ from `defaults`, to be saved as overrides.
+    pub fn overrides(&self, defaults: &Self) -> Self {
+        Self(
+            self.0
+                .iter()
+                .filter(|&(action, key)| defaults.0.get(action) != Some(key))
+                .map(|(&action, &key)| (action, key))
+                .collect(),
+        )
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), SettingsError> {
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, contents)?;
+
+        Ok(())
+    }
+
+    pub fn key(&self, action: TunerAction) -> KeyCode {
+        self.0[&action]
+    }
+
+    /// Presses made while Ctrl is held are left to shortcuts such as undo and redo.
+    pub fn just_pressed(&self, keycode: &ButtonInput<KeyCode>, action: TunerAction) -> bool {
+        !ctrl_pressed(keycode) && keycode.just_pressed(self.key(action))
+    }
+
+    /// Whether the action's key was just pressed while Ctrl is held.
+    pub fn shortcut_just_pressed(
+        &self,
+        keycode: &ButtonInput<KeyCode>,
+        action: TunerAction,
+    ) -> bool {
+        ctrl_pressed(keycode) && keycode.just_pressed(self.key(action))
+    }
+
+    /// The "(P/;)" style label shown in the HUD for a pair of opposing actions.
+    pub fn pair_label(
+        &self,
+        first: TunerAction,
+        second: TunerAction,
+        layout: &KeyboardLayout,
+    ) -> String {
+        format!(
+            "({}/{})",
+            layout.key_label(self.key(first)),
+            layout.key_label(self.key(second))
+        )
+    }
+
+    pub fn label(&self, action: TunerAction, layout: &KeyboardLayout) -> String {
+        layout.key_label(self.key(action))
+    }
+
+    pub fn shortcut_label(&self, action: TunerAction, layout: &KeyboardLayout) -> String {
+        format!("Ctrl+{}", self.label(action, layout))
+    }
+}
+
+fn ctrl_pressed(keycode: &ButtonInput<KeyCode>) -> bool {
+    keycode.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+}
+
+/// The character each physical key types on the user's keyboard layout, learned from the keys
+/// pressed so far, so the HUD can show "A" rather than "Q" on AZERTY. Keys which have not been
+/// pressed yet are labelled by their position on a US keyboard.
+#[derive(Resource, Default, Debug, Clone)]
+pub struct KeyboardLayout(HashMap<KeyCode, String>);
+
+impl KeyboardLayout {
+    pub fn key_label(&self, key_code: KeyCode) -> String {
+        self.0
+            .get(&key_code)
+            .cloned()
+            .unwrap_or_else(|| key_label(key_code))
+    }
+}
+
+/// Modifiers change the character a key types, so presses made while they are held are not
+/// learned.
+const LAYOUT_MODIFIERS: [KeyCode; 6] = [
+    KeyCode::ShiftLeft,
+    KeyCode::ShiftRight,
+    KeyCode::ControlLeft,
+    KeyCode::ControlRight,
+    KeyCode::AltLeft,
+    KeyCode::AltRight,
+];
+
+/// Records the character typed by each key pressed. Modifiers are tracked from the events rather
+/// than [`ButtonInput`], which modal screens clear.
+pub(crate) fn learn_keyboard_layout(
+    mut events: EventReader<KeyboardInput>,
+    mut held_modifiers: Local<Vec<KeyCode>>,
+    mut layout: ResMut<KeyboardLayout>,
+) {
+    for event in events.read() {
+        if LAYOUT_MODIFIERS.contains(&event.key_code) {
+            held_modifiers.retain(|&key| key != event.key_code);
+            if event.state == ButtonState::Pressed {
+                held_modifiers.push(event.key_code);
+            }
+            continue;
+        }
+        if event.state != ButtonState::Pressed || !held_modifiers.is_empty() {
+            continue;
+        }
+
+        let character = match &event.logical_key {
+            Key::Character(character) => character.to_uppercase(),
+            // Keys such as ^ on French layouts only type once followed by another key.
+            Key::Dead(Some(character)) => character.to_uppercase().to_string(),
+            _ => continue,
+        };
+        if character.trim().is_empty() || character.chars().any(char::is_control) {
+            continue;
+        }
+
+        if layout.0.get(&event.key_code) != Some(&character) {
+            layout.0.insert(event.key_code, character);
+        }
+    }
+}
+
+/// A short human readable name for a physical key.
+pub fn key_label(key_code: KeyCode) -> String {
+    let label = match key_code {
+        KeyCode::Space => "Space",
+        KeyCode::Semicolon => ";",
+        KeyCode::Quote => "'",
+        KeyCode::Comma => ",",
+        KeyCode::Period => ".",
+        KeyCode::Slash => "/",
+        KeyCode::Backslash => "\\",
+        KeyCode::BracketLeft => "[",
+        KeyCode::BracketRight => "]",
+        KeyCode::Minus => "-",
+        KeyCode::Equal => "=",
+        KeyCode::Backquote => "`",
+        _ => {
+            let name = format!("{key_code:?}");
+
+            return ["Key", "Digit"]
+                .into_iter()
+                .find_map(|prefix| name.strip_prefix(prefix))
+                .map_or(name.clone(), str::to_string);
+        }
+    };
+
+    label.to_string()
+}
+
+// ------------------------------------------------------------------------------------------------
+
+/// The rate multiplier gained for each second an action is held.
+const HOLD_ACCELERATION: f32 = 1.5;
+const MAX_HOLD_MULTIPLIER: f32 = 10.0;
+
+const COARSE_MULTIPLIER: f32 = 10.0;
+const FINE_MULTIPLIER: f32 = 0.1;
+
+/// Tracks how long each bound action has been held, so continuous adjustments speed up the longer
+/// a key stays down.
+#[derive(Default)]
+pub struct HeldActions(HashMap<TunerAction, f32>);
+
+impl HeldActions {
+    pub fn update(&mut self, bindings: &TunerBindings, keycode: &ButtonInput<KeyCode>, dt: f32) {
+        for (&action, &key) in &bindings.0 {
+            if keycode.pressed(key) {
+                *self.0.entry(action).or_default() += dt;
+            } else {
+                self.0.remove(&action);
+            }
+        }
+    }
+
+    /// The accelerated rate multiplier of a held action, or zero if it is not held.
+    pub fn rate(&self, action: TunerAction) -> f32 {
+        self.0
+            .get(&action)
+            .map_or(0.0, |&held_for| hold_multiplier(held_for))
+    }
+
+    /// The signed rate of a pair of opposing actions.
+    pub fn axis(&self, up: TunerAction, down: TunerAction) -> f32 {
+        self.rate(up) - self.rate(down)
+    }
+}
+
+/// How much faster an adjustment is after being held for `held_for` seconds.
+pub fn hold_multiplier(held_for: f32) -> f32 {
+    (1.0 + held_for * HOLD_ACCELERATION).min(MAX_HOLD_MULTIPLIER)
+}
+
+/// Shift makes adjustments coarse and Ctrl makes them fine.
+pub fn step_modifier(keycode: &ButtonInput<KeyCode>) -> f32 {
+    if keycode.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        COARSE_MULTIPLIER
+    } else if ctrl_pressed(keycode) {
+        FINE_MULTIPLIER
+    } else {
+        1.0
+    }
+}
@@ -0,0 +1,229 @@
+//! Split-screen A/B comparison: a second camera follows the [`TunerCamera`] and renders the right
+//! half of the window with its own bloom and tonemapping, so two configurations can be judged side
+//! by side.
+
+use bevy::core_pipeline::{bloom::BloomSettings, tonemapping::Tonemapping};
+use bevy::prelude::*;
+use bevy::render::camera::{ClearColorConfig, Viewport};
+use bevy::window::PrimaryWindow;
+
+use crate::bindings::{KeyboardLayout, TunerAction, TunerBindings};
+use crate::tuner::TunerCamera;
+
+/// The camera rendering the right (B) side of the comparison.
+#[derive(Component)]
+pub struct ComparisonCamera;
+
+/// The camera rendering the left (A) side of the comparison, which the B side follows.
+#[derive(Component)]
+pub struct ComparisonPrimary;
+
+/// Draws the UI over the whole window while the scene cameras each render half of it.
+#[derive(Component)]
+pub struct ComparisonUiCamera;
+
+/// Marks the text entity describing the comparison.
+#[derive(Component)]
+pub struct ComparisonHud;
+
+// ------------------------------------------------------------------------------------------------
+
+pub(crate) fn setup_comparison_hud(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 20.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            right: Val::Px(12.0),
+            ..default()
+        }),
+        ComparisonHud,
+    ));
+}
+
+type CameraStateQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Camera,
+        &'static Transform,
+        &'static Projection,
+        &'static Tonemapping,
+        Option<&'static BloomSettings>,
+        Has<TunerCamera>,
+    ),
+>;
+
+/// Starts and stops the comparison, moves the tuner between the two sides, and copies the tuned
+/// side's settings to the other side.
+pub(crate) fn handle_comparison_keys(
+    mut commands: Commands,
+    keycode: Res<ButtonInput<KeyCode>>,
+    bindings: Res<TunerBindings>,
+    cameras: CameraStateQuery,
+    primary: Query<Entity, With<ComparisonPrimary>>,
+    secondary: Query<Entity, With<ComparisonCamera>>,
+    ui_camera: Query<Entity, With<ComparisonUiCamera>>,
+) {
+    use TunerAction::*;
+
+    let sides = primary.get_single().ok().zip(secondary.get_single().ok());
+
+    if bindings.just_pressed(&keycode, ToggleComparison) {
+        match sides {
+            Some((a, b)) => {
+                let Ok((_, camera, ..)) = cameras.get(a) else {
+                    return;
+                };
+
+                commands.entity(a).remove::<ComparisonPrimary>().insert((
+                    Camera {
+                        viewport: None,
+                        ..camera.clone()
+                    },
+                    TunerCamera,
+                ));
+                commands.entity(b).despawn_recursive();
+                for entity in &ui_camera {
+                    commands.entity(entity).despawn_recursive();
+                }
+            }
+            None => {
+                let Some((a, camera, transform, projection, tonemapping, bloom_settings, _)) =
+                    cameras.iter().find(|(.., is_tuned)| *is_tuned)
+                else {
+                    return;
+                };
+
+                commands.entity(a).insert(ComparisonPrimary);
+
+                let mut b = commands.spawn((
+                    Camera3dBundle {
+                        camera: Camera {
+                            hdr: camera.hdr,
+                            order: camera.order + 1,
+                            ..default()
+                        },
+                        projection: projection.clone(),
+                        tonemapping: *tonemapping,
+                        transform: *transform,
+                        ..default()
+                    },
+                    ComparisonCamera,
+                ));
+                if let Some(bloom_settings) = bloom_settings {
+                    b.insert(bloom_settings.clone());
+                }
+
+                commands.spawn((
+                    Camera2dBundle {
+                        camera: Camera {
+                            order: camera.order + 2,
+                            clear_color: ClearColorConfig::None,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    IsDefaultUiCamera,
+                    ComparisonUiCamera,
+                ));
+            }
+        }
+
+        return;
+    }
+
+    let Some((a, b)) = sides else {
+        return;
+    };
+    let a_is_tuned = cameras.get(a).is_ok_and(|(.., is_tuned)| is_tuned);
+    let (tuned, other) = if a_is_tuned { (a, b) } else { (b, a) };
+
+    if bindings.just_pressed(&keycode, SwitchComparisonSide) {
+        commands.entity(tuned).remove::<TunerCamera>();
+        commands.entity(other).insert(TunerCamera);
+    }
+
+    if bindings.just_pressed(&keycode, CopyToOtherSide) {
+        let Ok((_, _, _, projection, tonemapping, bloom_settings, _)) = cameras.get(tuned) else {
+            return;
+        };
+
+        let mut other = commands.entity(other);
+        other.insert((projection.clone(), *tonemapping));
+        match bloom_settings {
+            Some(bloom_settings) => other.insert(bloom_settings.clone()),
+            None => other.remove::<BloomSettings>(),
+        };
+    }
+}
+
+/// Keeps the B side looking from the same place as the A side, and splits the window between them.
+#[allow(clippy::type_complexity)]
+pub(crate) fn sync_comparison_cameras(
+    window: Query<&Window, With<PrimaryWindow>>,
+    mut primary: Query<
+        (&Transform, &mut Camera),
+        (With<ComparisonPrimary>, Without<ComparisonCamera>),
+    >,
+    mut secondary: Query<(&mut Transform, &mut Camera), With<ComparisonCamera>>,
+) {
+    let (Ok(window), Ok((a_transform, mut a_camera)), Ok((mut b_transform, mut b_camera))) = (
+        window.get_single(),
+        primary.get_single_mut(),
+        secondary.get_single_mut(),
+    ) else {
+        return;
+    };
+
+    *b_transform = *a_transform;
+
+    let half_size = UVec2::new(window.physical_width() / 2, window.physical_height());
+    a_camera.viewport = Some(Viewport {
+        physical_position: UVec2::ZERO,
+        physical_size: half_size,
+        ..default()
+    });
+    b_camera.viewport = Some(Viewport {
+        physical_position: UVec2::new(half_size.x, 0),
+        physical_size: half_size,
+        ..default()
+    });
+}
+
+pub(crate) fn update_comparison_hud(
+    mut text: Query<&mut Text, With<ComparisonHud>>,
+    bindings: Res<TunerBindings>,
+    layout: Res<KeyboardLayout>,
+    primary: Query<Has<TunerCamera>, With<ComparisonPrimary>>,
+) {
+    use TunerAction::*;
+
+    let Ok(mut text) = text.get_single_mut() else {
+        return;
+    };
+    let text = &mut text.sections[0].value;
+
+    *text = match primary.get_single() {
+        Ok(a_is_tuned) => format!(
+            "A/B comparison (Toggle: {})\n({}) Tuning side: {}\n({}) Copy to side {}\n",
+            bindings.label(ToggleComparison, &layout),
+            bindings.label(SwitchComparisonSide, &layout),
+            if a_is_tuned { "A (left)" } else { "B (right)" },
+            bindings.label(CopyToOtherSide, &layout),
+            if a_is_tuned { "B" } else { "A" },
+        ),
+        Err(_) => format!(
+            "A/B comparison: Off (Toggle: {})\n",
+            bindings.label(ToggleComparison, &layout)
+        ),
+    };
+}
@@ -0,0 +1,350 @@
+//! Checking that no two actions share a key, across the tuner's [`TunerBindings`] and bevy_flycam's
+//! [`KeyBindings`], which both read the same keyboard.
+
+use bevy::prelude::*;
+use bevy_flycam::prelude::KeyBindings;
+use std::{collections::BTreeMap, fmt};
+
+use crate::bindings::{KeyboardLayout, TunerAction, TunerBindings};
+use crate::user_settings::FlycamKeys;
+
+/// A movement of the flycam, bound in its [`KeyBindings`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FlycamAction {
+    MoveForward,
+    MoveBackward,
+    MoveLeft,
+    MoveRight,
+    MoveAscend,
+    MoveDescend,
+    ToggleGrabCursor,
+}
+
+impl FlycamAction {
+    pub const ALL: [Self; 7] = [
+        Self::MoveForward,
+        Self::MoveBackward,
+        Self::MoveLeft,
+        Self::MoveRight,
+        Self::MoveAscend,
+        Self::MoveDescend,
+        Self::ToggleGrabCursor,
+    ];
+
+    pub fn key(self, bindings: &KeyBindings) -> KeyCode {
+        match self {
+            Self::MoveForward => bindings.move_forward,
+            Self::MoveBackward => bindings.move_backward,
+            Self::MoveLeft => bindings.move_left,
+            Self::MoveRight => bindings.move_right,
+            Self::MoveAscend => bindings.move_ascend,
+            Self::MoveDescend => bindings.move_descend,
+            Self::ToggleGrabCursor => bindings.toggle_grab_cursor,
+        }
+    }
+
+    pub fn set_key(self, bindings: &mut KeyBindings, key: KeyCode) {
+        let field = match self {
+            Self::MoveForward => &mut bindings.move_forward,
+            Self::MoveBackward => &mut bindings.move_backward,
+            Self::MoveLeft => &mut bindings.move_left,
+            Self::MoveRight => &mut bindings.move_right,
+            Self::MoveAscend => &mut bindings.move_ascend,
+            Self::MoveDescend => &mut bindings.move_descend,
+            Self::ToggleGrabCursor => &mut bindings.toggle_grab_cursor,
+        };
+        *field = key;
+    }
+}
+
+/// Anything a key can be claimed by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BoundAction {
+    Tuner(TunerAction),
+    Flycam(FlycamAction),
+    /// Shift, Ctrl and Alt change how other keys and the mouse wheel behave, so they cannot be
+    /// bound to anything else.
+    Modifier,
+}
+
+impl fmt::Display for BoundAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tuner(action) => write!(f, "{action:?}"),
+            Self::Flycam(action) => write!(f, "flycam {action:?}"),
+            Self::Modifier => write!(f, "modifier"),
+        }
+    }
+}
+
+/// The keys reserved as modifiers.
+const MODIFIER_KEYS: [KeyCode; 6] = [
+    KeyCode::ShiftLeft,
+    KeyCode::ShiftRight,
+    KeyCode::ControlLeft,
+    KeyCode::ControlRight,
+    KeyCode::AltLeft,
+    KeyCode::AltRight,
+];
+
+/// Several actions triggered by the same key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyConflict {
+    pub key: KeyCode,
+    pub actions: Vec<BoundAction>,
+}
+
+impl KeyConflict {
+    /// Describes the conflict, naming the key by the character it types on `layout`.
+    pub fn label(&self, layout: &KeyboardLayout) -> String {
+        let actions = self
+            .actions
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!("{} is bound to {actions}", layout.key_label(self.key))
+    }
+}
+
+impl fmt::Display for KeyConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.label(&KeyboardLayout::default()))
+    }
+}
+
+impl std::error::Error for KeyConflict {}
+
+/// Whether two actions bound to the same key would both trigger. Tuner shortcuts are only
+/// triggered with Ctrl and other tuner actions only without it, but the flycam ignores Ctrl.
+fn clash(a: BoundAction, b: BoundAction) -> bool {
+    match (a, b) {
+        (BoundAction::Tuner(a), BoundAction::Tuner(b)) => a.is_shortcut() == b.is_shortcut(),
+        _ => true,
+    }
+}
+
+/// Every key claimed by more than one action which would trigger together.
+pub fn find_conflicts(tuner: &TunerBindings, flycam: Option<&KeyBindings>) -> Vec<KeyConflict> {
+    let mut by_key = BTreeMap::<KeyCode, Vec<BoundAction>>::new();
+
+    for (&action, &key) in &tuner.0 {
+        by_key
+            .entry(key)
+            .or_default()
+            .push(BoundAction::Tuner(action));
+    }
+    if let Some(flycam) = flycam {
+        for action in FlycamAction::ALL {
+            by_key
+                .entry(action.key(flycam))
+                .or_default()
+                .push(BoundAction::Flycam(action));
+        }
+    }
+    for key in MODIFIER_KEYS {
+        if let Some(actions) = by_key.get_mut(&key) {
+            actions.push(BoundAction::Modifier);
+        }
+    }
+
+    by_key
+        .into_iter()
+        .filter_map(|(key, actions)| {
+            let clashing: Vec<_> = actions
+                .iter()
+                .copied()
+                .filter(|&a| actions.iter().any(|&b| a != b && clash(a, b)))
+                .collect();
+
+            (!clashing.is_empty()).then_some(KeyConflict {
+                key,
+                actions: clashing,
+            })
+        })
+        .collect()
+}
+
+/// Binds `action` to `key` unless that would make it trigger together with another action, in
+/// which case the bindings are left as they were.
+pub fn try_rebind(
+    tuner: &mut TunerBindings,
+    flycam: Option<&mut KeyBindings>,
+    action: BoundAction,
+    key: KeyCode,
+) -> Result<(), KeyConflict> {
+    let mut new_tuner = tuner.clone();
+    let mut new_flycam = flycam
+        .as_deref()
+        .map(|flycam| KeyBindings::from(FlycamKeys::from(flycam)));
+
+    match (action, new_flycam.as_mut()) {
+        (BoundAction::Tuner(action), _) => {
+            new_tuner.0.insert(action, key);
+        }
+        (BoundAction::Flycam(action), Some(new_flycam)) => action.set_key(new_flycam, key),
+        (BoundAction::Flycam(_), None) | (BoundAction::Modifier, _) => {
+            return Err(KeyConflict {
+                key,
+                actions: vec![action],
+            })
+        }
+    }
+
+    if let Some(conflict) = find_conflicts(&new_tuner, new_flycam.as_ref())
+        .into_iter()
+        .find(|conflict| conflict.actions.contains(&action))
+    {
+        return Err(conflict);
+    }
+
+    *tuner = new_tuner;
+    if let (Some(flycam), Some(new_flycam)) = (flycam, new_flycam) {
+        *flycam = new_flycam;
+    }
+
+    Ok(())
+}
+
+/// The conflicts found the last time either set of bindings changed.
+#[derive(Resource, Default, Debug)]
+pub struct KeyConflicts(pub Vec<KeyConflict>);
+
+// ------------------------------------------------------------------------------------------------
+
+/// Checks the bindings at startup and whenever they change, logging every conflict found.
+pub(crate) fn check_key_conflicts(
+    tuner: Res<TunerBindings>,
+    flycam: Option<Res<KeyBindings>>,
+    mut conflicts: ResMut<KeyConflicts>,
+) {
+    if !tuner.is_changed() && !flycam.as_ref().is_some_and(|flycam| flycam.is_changed()) {
+        return;
+    }
+
+    conflicts.0 = find_conflicts(&tuner, flycam.as_deref());
+    for conflict in &conflicts.0 {
+        warn!("Key binding conflict: {conflict}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The flycam keys the app starts with, which do not conflict with the default tuner keys.
+    fn default_flycam() -> KeyBindings {
+        KeyBindings::from(FlycamKeys::default())
+    }
+
+    #[test]
+    fn shortcut_sharing_a_key_with_a_plain_action_does_not_conflict() {
+        let tuner = TunerBindings::default();
+        assert_eq!(
+            tuner.key(TunerAction::Redo),
+            tuner.key(TunerAction::CompositeModeAdditive)
+        );
+
+        assert_eq!(find_conflicts(&tuner, Some(&default_flycam())), vec![]);
+    }
+
+    #[test]
+    fn shortcuts_sharing_a_key_conflict() {
+        let mut tuner = TunerBindings::default();
+        let key = tuner.key(TunerAction::Undo);
+
+        let conflict =
+            try_rebind(&mut tuner, None, BoundAction::Tuner(TunerAction::Redo), key).unwrap_err();
+        assert_eq!(
+            conflict.actions,
+            vec![
+                BoundAction::Tuner(TunerAction::Undo),
+                BoundAction::Tuner(TunerAction::Redo),
+            ]
+        );
+    }
+
+    #[test]
+    fn flycam_key_conflicts_with_shortcut() {
+        let tuner = TunerBindings::default();
+        let mut flycam = default_flycam();
+        flycam.move_forward = tuner.key(TunerAction::Undo);
+
+        assert_eq!(
+            find_conflicts(&tuner, Some(&flycam)),
+            vec![KeyConflict {
+                key: KeyCode::KeyZ,
+                actions: vec![
+                    BoundAction::Tuner(TunerAction::Undo),
+                    BoundAction::Flycam(FlycamAction::MoveForward),
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn modifier_keys_are_refused() {
+        let mut tuner = TunerBindings::default();
+        let mut flycam = default_flycam();
+
+        for (action, key) in [
+            (
+                BoundAction::Tuner(TunerAction::ToggleBloom),
+                KeyCode::ShiftLeft,
+            ),
+            (BoundAction::Tuner(TunerAction::Undo), KeyCode::ControlRight),
+            (
+                BoundAction::Flycam(FlycamAction::MoveAscend),
+                KeyCode::AltLeft,
+            ),
+        ] {
+            let conflict = try_rebind(&mut tuner, Some(&mut flycam), action, key).unwrap_err();
+            assert_eq!(conflict.key, key);
+            assert_eq!(conflict.actions, vec![action, BoundAction::Modifier]);
+        }
+
+        // bevy_flycam's own defaults descend with Shift.
+        assert!(find_conflicts(&tuner, Some(&KeyBindings::default()))
+            .iter()
+            .any(|conflict| conflict.key == KeyCode::ShiftLeft
+                && conflict.actions.contains(&BoundAction::Modifier)));
+    }
+
+    #[test]
+    fn refused_rebind_leaves_bindings_unchanged() {
+        let mut tuner = TunerBindings::default();
+        let mut flycam = default_flycam();
+
+        for (action, key) in [
+            (
+                BoundAction::Tuner(TunerAction::IntensityUp),
+                flycam.move_forward,
+            ),
+            (
+                BoundAction::Flycam(FlycamAction::MoveForward),
+                tuner.key(TunerAction::IntensityUp),
+            ),
+        ] {
+            assert!(try_rebind(&mut tuner, Some(&mut flycam), action, key).is_err());
+            assert_eq!(tuner.0, TunerBindings::default().0);
+            assert_eq!(FlycamKeys::from(&flycam), FlycamKeys::default());
+        }
+    }
+
+    #[test]
+    fn accepted_rebind_changes_only_its_action() {
+        let mut tuner = TunerBindings::default();
+        let mut flycam = default_flycam();
+
+        try_rebind(
+            &mut tuner,
+            Some(&mut flycam),
+            BoundAction::Flycam(FlycamAction::MoveForward),
+            KeyCode::ArrowUp,
+        )
+        .unwrap();
+        assert_eq!(flycam.move_forward, KeyCode::ArrowUp);
+        assert_eq!(tuner.0, TunerBindings::default().0);
+    }
+}
@@ -0,0 +1,132 @@
+//! A panel of frame timings and entity counts, to see what the current bloom settings cost.
+
+use bevy::diagnostic::{
+    DiagnosticsStore, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin,
+};
+use bevy::prelude::*;
+
+use crate::bindings::{KeyboardLayout, TunerAction, TunerBindings};
+use crate::sphere_grid::GridSphere;
+
+/// Shows FPS, frame times, and entity and sphere counts next to the bloom HUD, adding Bevy's
+/// frame time and entity count diagnostics if they are not already present. Requires
+/// [`BloomTunerPlugin`](crate::BloomTunerPlugin) for the toggle key.
+pub struct DiagnosticsOverlayPlugin;
+
+impl Plugin for DiagnosticsOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin);
+        }
+        if !app.is_plugin_added::<EntityCountDiagnosticsPlugin>() {
+            app.add_plugins(EntityCountDiagnosticsPlugin);
+        }
+
+        app.add_systems(Startup, setup_diagnostics_hud).add_systems(
+            Update,
+            (toggle_diagnostics_hud, update_diagnostics_hud).chain(),
+        );
+    }
+}
+
+/// Marks the text entity listing the diagnostics.
+#[derive(Component)]
+pub struct DiagnosticsHud;
+
+// ------------------------------------------------------------------------------------------------
+
+fn setup_diagnostics_hud(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 20.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(12.0),
+            right: Val::Px(12.0),
+            ..default()
+        }),
+        DiagnosticsHud,
+    ));
+}
+
+fn toggle_diagnostics_hud(
+    keycode: Res<ButtonInput<KeyCode>>,
+    bindings: Res<TunerBindings>,
+    mut hud: Query<&mut Visibility, With<DiagnosticsHud>>,
+) {
+    if !bindings.just_pressed(&keycode, TunerAction::ToggleDiagnostics) {
+        return;
+    }
+
+    for mut visibility in hud.iter_mut() {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Inherited,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+fn update_diagnostics_hud(
+    mut text: Query<(&mut Text, &Visibility), With<DiagnosticsHud>>,
+    diagnostics: Res<DiagnosticsStore>,
+    spheres: Query<(), With<GridSphere>>,
+    bindings: Res<TunerBindings>,
+    layout: Res<KeyboardLayout>,
+) {
+    let Ok((mut text, visibility)) = text.get_single_mut() else {
+        return;
+    };
+    if *visibility == Visibility::Hidden {
+        return;
+    }
+    let text = &mut text.sections[0].value;
+
+    *text = format!(
+        "Diagnostics (Toggle: {})\n",
+        bindings.label(TunerAction::ToggleDiagnostics, &layout)
+    );
+
+    if let Some(fps) = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+    {
+        text.push_str(&format!("FPS: {fps:.1}\n"));
+    }
+
+    if let Some(frame_time) = diagnostics.get(&FrameTimeDiagnosticsPlugin::FRAME_TIME) {
+        let min = frame_time.values().copied().reduce(f64::min);
+        let max = frame_time.values().copied().reduce(f64::max);
+
+        if let (Some(min), Some(average), Some(max)) = (min, frame_time.average(), max) {
+            text.push_str(&format!(
+                "Frame time (last {} frames): {min:.2}/{average:.2}/{max:.2} ms (min/avg/max)\n",
+                frame_time.history_len()
+            ));
+        }
+    }
+
+    if let Some(entities) = diagnostics
+        .get(&EntityCountDiagnosticsPlugin::ENTITY_COUNT)
+        .and_then(|entities| entities.value())
+    {
+        text.push_str(&format!("Entities: {entities}\n"));
+    }
+
+    text.push_str(&format!("Spheres: {}\n", spheres.iter().len()));
+
+    // Systems which time themselves register their diagnostics under `systems/`.
+    for diagnostic in diagnostics.iter() {
+        let Some(system) = diagnostic.path().as_str().strip_prefix("systems/") else {
+            continue;
+        };
+        if let Some(average) = diagnostic.average() {
+            text.push_str(&format!("{system}: {average:.3} {}\n", diagnostic.suffix));
+        }
+    }
+}
@@ -0,0 +1,268 @@
+//! Optional scene dressing, a ground plane, a skybox with matching environment lighting and
+//! adjustable ambient light, to see how bloom behaves in scenes which are not dark.
+
+use bevy::core_pipeline::Skybox;
+use bevy::prelude::*;
+
+use crate::bindings::{step_modifier, HeldActions, TunerAction, TunerBindings};
+use crate::sphere_grid::SphereGridConfig;
+
+/// How much a held key changes the ambient brightness per second, in cd/m², before acceleration
+/// and modifiers.
+const AMBIENT_ADJUST_RATE: f32 = 200.0;
+
+/// Adds a ground plane under the sphere grid, a skybox and environment map loaded from KTX2
+/// cubemaps in the assets folder, and keys to toggle them and change the [`AmbientLight`].
+/// Requires [`BloomTunerPlugin`](crate::BloomTunerPlugin) for the key bindings.
+///
+/// No cubemaps ship with the app, so the skybox can only be shown once
+/// [`with_environment_maps`](Self::with_environment_maps) has been given some, such as
+/// `pisa_diffuse_rgb9e5_zstd.ktx2` and `pisa_specular_rgb9e5_zstd.ktx2` from
+/// `assets/environment_maps` in the Bevy repository.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use application::EnvironmentPlugin;
+/// App::new().add_plugins(
+///     EnvironmentPlugin::new()
+///         .with_environment_maps(
+///             "environment_maps/pisa_diffuse_rgb9e5_zstd.ktx2",
+///             "environment_maps/pisa_specular_rgb9e5_zstd.ktx2",
+///         )
+///         .with_skybox(true),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct EnvironmentPlugin {
+    settings: SceneEnvironment,
+}
+
+/// Which parts of the scene dressing are shown, available as a resource once
+/// [`EnvironmentPlugin`] is added.
+#[derive(Resource, Clone, Debug)]
+pub struct SceneEnvironment {
+    pub ground: bool,
+    /// Shows the skybox and lights the scene with the environment map. Only takes effect when
+    /// `environment_maps` is set.
+    pub skybox: bool,
+    pub environment_maps: Option<EnvironmentMapPaths>,
+    /// Brightness of the skybox and environment lighting, in cd/m².
+    pub intensity: f32,
+    /// Brightness of the [`AmbientLight`], in cd/m², which is kept in step with this.
+    pub ambient_brightness: f32,
+}
+
+impl Default for SceneEnvironment {
+    fn default() -> Self {
+        Self {
+            ground: false,
+            skybox: false,
+            environment_maps: None,
+            intensity: 1000.0,
+            ambient_brightness: AmbientLight::default().brightness,
+        }
+    }
+}
+
+/// Where the environment cubemaps are, relative to the assets folder.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EnvironmentMapPaths {
+    /// Cubemap of the light arriving from each direction, blurred.
+    pub diffuse: String,
+    /// Cubemap of the surroundings, mipmapped. Also used as the skybox.
+    pub specular: String,
+}
+
+impl Default for EnvironmentPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EnvironmentPlugin {
+    pub fn new() -> Self {
+        Self {
+            settings: SceneEnvironment::default(),
+        }
+    }
+
+    pub fn with_ground(mut self, ground: bool) -> Self {
+        self.settings.ground = ground;
+        self
+    }
+
+    pub fn with_skybox(mut self, skybox: bool) -> Self {
+        self.settings.skybox = skybox;
+        self
+    }
+
+    /// Loads the diffuse and specular environment cubemaps from these paths in the assets folder.
+    pub fn with_environment_maps(
+        mut self,
+        diffuse_map: impl Into<String>,
+        specular_map: impl Into<String>,
+    ) -> Self {
+        self.settings.environment_maps = Some(EnvironmentMapPaths {
+            diffuse: diffuse_map.into(),
+            specular: specular_map.into(),
+        });
+        self
+    }
+
+    pub fn with_ambient_brightness(mut self, brightness: f32) -> Self {
+        self.settings.ambient_brightness = brightness;
+        self
+    }
+}
+
+impl Plugin for EnvironmentPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.settings.clone())
+            .add_systems(Startup, setup_environment)
+            .add_systems(Update, (handle_environment_keys, apply_environment).chain());
+    }
+}
+
+/// Marks the ground plane.
+#[derive(Component)]
+pub struct GroundPlane;
+
+/// The environment cubemaps, loaded the first time the skybox is shown.
+struct EnvironmentMaps {
+    diffuse: Handle<Image>,
+    specular: Handle<Image>,
+}
+
+// ------------------------------------------------------------------------------------------------
+
+fn setup_environment(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    settings: Res<SceneEnvironment>,
+    grid_config: Option<Res<SphereGridConfig>>,
+) {
+    // Lay the ground just under the lowest point the spheres reach, with some room to spare
+    // around the grid.
+    let (height, size) = match grid_config {
+        Some(grid) => {
+            let height = match grid.physics {
+                Some(physics) => physics.ground_height,
+                None => -(grid.motion.amplitude.abs() + grid.radius),
+            };
+            let size = grid.size.as_vec2() * grid.spacing + Vec2::splat(20.0);
+
+            (height, size)
+        }
+        None => (0.0, Vec2::splat(100.0)),
+    };
+
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(Plane3d::default().mesh().size(size.x, size.y)),
+            material: materials.add(StandardMaterial {
+                base_color: Color::rgb(0.3, 0.3, 0.3),
+                perceptual_roughness: 0.8,
+                ..default()
+            }),
+            transform: Transform::from_xyz(0.0, height, 0.0),
+            visibility: if settings.ground {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            },
+            ..default()
+        },
+        GroundPlane,
+    ));
+}
+
+fn handle_environment_keys(
+    keycode: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    bindings: Res<TunerBindings>,
+    mut held: Local<HeldActions>,
+    mut settings: ResMut<SceneEnvironment>,
+) {
+    use TunerAction::*;
+
+    held.update(&bindings, &keycode, time.delta_seconds());
+
+    if bindings.just_pressed(&keycode, ToggleGround) {
+        settings.ground = !settings.ground;
+    }
+
+    if bindings.just_pressed(&keycode, ToggleSkybox) {
+        if settings.environment_maps.is_some() {
+            settings.skybox = !settings.skybox;
+        } else {
+            warn!("No environment maps were given, so there is no skybox to show");
+        }
+    }
+
+    let dt = time.delta_seconds() * step_modifier(&keycode);
+    let change = held.axis(AmbientUp, AmbientDown) * dt * AMBIENT_ADJUST_RATE;
+    if change != 0.0 {
+        settings.ambient_brightness = (settings.ambient_brightness + change).max(0.0);
+    }
+}
+
+/// Shows or hides the ground, sets the ambient light, and adds or removes the skybox on every 3D
+/// camera, including ones spawned after it was switched on.
+fn apply_environment(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<SceneEnvironment>,
+    mut ambient_light: ResMut<AmbientLight>,
+    mut maps: Local<Option<EnvironmentMaps>>,
+    mut ground: Query<&mut Visibility, With<GroundPlane>>,
+    cameras: Query<(Entity, Has<Skybox>), With<Camera3d>>,
+) {
+    if ambient_light.brightness != settings.ambient_brightness {
+        ambient_light.brightness = settings.ambient_brightness;
+    }
+
+    let wanted = if settings.ground {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    for mut visibility in ground.iter_mut() {
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+    }
+
+    let paths = settings
+        .environment_maps
+        .as_ref()
+        .filter(|_| settings.skybox);
+    for (entity, has_skybox) in &cameras {
+        match (paths, has_skybox) {
+            (Some(paths), false) => {
+                let maps = maps.get_or_insert_with(|| EnvironmentMaps {
+                    diffuse: asset_server.load(&paths.diffuse),
+                    specular: asset_server.load(&paths.specular),
+                });
+
+                commands.entity(entity).insert((
+                    Skybox {
+                        image: maps.specular.clone(),
+                        brightness: settings.intensity,
+                    },
+                    EnvironmentMapLight {
+                        diffuse_map: maps.diffuse.clone(),
+                        specular_map: maps.specular.clone(),
+                        intensity: settings.intensity,
+                    },
+                ));
+            }
+            (None, true) => {
+                commands
+                    .entity(entity)
+                    .remove::<(Skybox, EnvironmentMapLight)>();
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//! Changing the flycam's speed and sensitivity at runtime, with keys and the mouse wheel, and
+//! sprinting while Shift is held.
+
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+use bevy_flycam::prelude::{KeyBindings, MovementSettings};
+use std::path::PathBuf;
+
+use crate::bindings::{HeldActions, TunerAction, TunerBindings};
+use crate::user_settings::{
+    SettingsError, UserSettings, DEFAULT_SETTINGS_PATH, DEFAULT_USER_SETTINGS_PATH,
+};
+
+/// How many times faster or slower a held key makes the flycam each second, before acceleration.
+const ADJUST_FACTOR_PER_SECOND: f32 = 2.0;
+/// How many times faster or slower each line scrolled makes the flycam.
+const ADJUST_FACTOR_PER_LINE: f32 = 1.1;
+/// How many pixels of touchpad scrolling count as one line.
+const PIXELS_PER_LINE: f32 = 50.0;
+
+const SPEED_RANGE: (f32, f32) = (0.1, 1000.0);
+const SENSITIVITY_RANGE: (f32, f32) = (0.000001, 0.01);
+
+/// Sets bevy_flycam's [`KeyBindings`] from the [`UserSettings`] and keeps its [`MovementSettings`]
+/// in step with them. The settings are loaded at startup, changed with keys and the mouse wheel
+/// (Alt + wheel for sensitivity) and saved with a key. Only settings changed at runtime are saved,
+/// so a speed or sensitivity given to the plugin lasts for that run only. Requires
+/// [`BloomTunerPlugin`](crate::BloomTunerPlugin) for the key bindings.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use application::FlycamControlPlugin;
+/// App::new().add_plugins(FlycamControlPlugin::new().with_speed(20.0));
+/// ```
+pub struct FlycamControlPlugin {
+    defaults_path: PathBuf,
+    settings_path: PathBuf,
+    speed: Option<f32>,
+    sensitivity: Option<f32>,
+}
+
+impl Default for FlycamControlPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FlycamControlPlugin {
+    pub fn new() -> Self {
+        Self {
+            defaults_path: PathBuf::from(DEFAULT_SETTINGS_PATH),
+            settings_path: PathBuf::from(DEFAULT_USER_SETTINGS_PATH),
+            speed: None,
+            sensitivity: None,
+        }
+    }
+
+    /// Loads the settings from this file when the user has not saved their own.
+    pub fn with_defaults_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.defaults_path = path.into();
+        self
+    }
+
+    /// Loads the user's own settings from this file, and saves them to it.
+    pub fn with_settings_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.settings_path = path.into();
+        self
+    }
+
+    /// Starts at this speed instead of the saved one.
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = Some(speed);
+        self
+    }
+
+    /// Starts with this sensitivity instead of the saved one.
+    pub fn with_sensitivity(mut self, sensitivity: f32) -> Self {
+        self.sensitivity = Some(sensitivity);
+        self
+    }
+}
+
+impl Plugin for FlycamControlPlugin {
+    fn build(&self, app: &mut App) {
+        let saved = UserSettings::load_user_or_defaults(&self.settings_path, &self.defaults_path);
+        let mut settings = saved.clone();
+        if let Some(speed) = self.speed {
+            settings.flycam.speed = speed;
+        }
+        if let Some(sensitivity) = self.sensitivity {
+            settings.flycam.sensitivity = sensitivity;
+        }
+
+        app.insert_resource(MovementSettings {
+            sensitivity: settings.flycam.sensitivity,
+            speed: settings.flycam.speed,
+        })
+        .insert_resource(KeyBindings::from(settings.flycam_keys))
+        .insert_resource(UserSettingsFile {
+            path: self.settings_path.clone(),
+            saved,
+            baseline: settings.clone(),
+        })
+        .insert_resource(settings)
+        .add_systems(
+            Update,
+            (adjust_flycam, apply_flycam_settings, save_user_settings).chain(),
+        );
+    }
+}
+
+/// Where the user's own [`UserSettings`] are saved, and what was last loaded or saved there.
+#[derive(Resource, Clone, Debug)]
+pub struct UserSettingsFile {
+    pub path: PathBuf,
+    /// The settings as last loaded or saved.
+    saved: UserSettings,
+    /// The settings in use at that point, including any given to the plugin.
+    baseline: UserSettings,
+}
+
+impl UserSettingsFile {
+    /// Saves the settings changed since they were last loaded or saved, keeping the saved values
+    /// of the rest.
+    pub fn save_changes(&mut self, settings: &UserSettings) -> Result<(), SettingsError> {
+        let saved = self.saved.clone().with_changes(&self.baseline, settings);
+        saved.save(&self.path)?;
+
+        self.saved = saved;
+        self.baseline = settings.clone();
+        Ok(())
+    }
+}
+
+/// Whether Shift is held, multiplying the flycam speed.
+pub fn sprinting(keycode: &ButtonInput<KeyCode>) -> bool {
+    keycode.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+}
+
+// ------------------------------------------------------------------------------------------------
+
+fn adjust_flycam(
+    keycode: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    bindings: Res<TunerBindings>,
+    mut held: Local<HeldActions>,
+    mut wheel: EventReader<MouseWheel>,
+    mut settings: ResMut<UserSettings>,
+) {
+    use TunerAction::*;
+
+    held.update(&bindings, &keycode, time.delta_seconds());
+
+    let lines: f32 = wheel
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / PIXELS_PER_LINE,
+        })
+        .sum();
+    let wheel_adjusts_sensitivity = keycode.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+
+    let dt = time.delta_seconds();
+    let mut speed_steps = held.axis(FlySpeedUp, FlySpeedDown) * dt;
+    let mut sensitivity_steps = held.axis(SensitivityUp, SensitivityDown) * dt;
+
+    // Convert the scrolled lines into the same units as the held keys.
+    let wheel_steps = lines * ADJUST_FACTOR_PER_LINE.ln() / ADJUST_FACTOR_PER_SECOND.ln();
+    if wheel_adjusts_sensitivity {
+        sensitivity_steps += wheel_steps;
+    } else {
+        speed_steps += wheel_steps;
+    }
+
+    // Scale rather than add, so the same key press makes a similar difference at any speed.
+    let flycam = &mut settings.flycam;
+    if speed_steps != 0.0 {
+        flycam.speed = (flycam.speed * ADJUST_FACTOR_PER_SECOND.powf(speed_steps))
+            .clamp(SPEED_RANGE.0, SPEED_RANGE.1);
+    }
+    if sensitivity_steps != 0.0 {
+        flycam.sensitivity = (flycam.sensitivity
+            * ADJUST_FACTOR_PER_SECOND.powf(sensitivity_steps))
+        .clamp(SENSITIVITY_RANGE.0, SENSITIVITY_RANGE.1);
+    }
+}
+
+fn apply_flycam_settings(
+    keycode: Res<ButtonInput<KeyCode>>,
+    settings: Res<UserSettings>,
+    mut movement: ResMut<MovementSettings>,
+) {
+    let flycam = settings.flycam;
+    let speed = if sprinting(&keycode) {
+        flycam.speed * flycam.sprint_multiplier
+    } else {
+        flycam.speed
+    };
+
+    if movement.speed != speed || movement.sensitivity != flycam.sensitivity {
+        movement.speed = speed;
+        movement.sensitivity = flycam.sensitivity;
+    }
+}
+
+fn save_user_settings(
+    keycode: Res<ButtonInput<KeyCode>>,
+    bindings: Res<TunerBindings>,
+    settings: Res<UserSettings>,
+    mut file: ResMut<UserSettingsFile>,
+) {
+    if !bindings.just_pressed(&keycode, TunerAction::SaveUserSettings) {
+        return;
+    }
+
+    match file.save_changes(&settings) {
+        Ok(()) => info!("Saved user settings to {}", file.path.display()),
+        Err(err) => error!(
+            "Could not save user settings to {}: {err}",
+            file.path.display()
+        ),
+    }
+}
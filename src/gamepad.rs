@@ -0,0 +1,167 @@
+//! Flying the camera and tuning bloom with a gamepad. The sticks move and turn the flycam, the
+//! triggers ascend and descend, and the D-pad picks a parameter and changes it.
+
+use bevy::core_pipeline::bloom::BloomSettings;
+use bevy::prelude::*;
+use bevy_flycam::prelude::{FlyCam, MovementSettings};
+
+use crate::bindings::hold_multiplier;
+use crate::parameters::TunerParameter;
+use crate::tuner::TunerCamera;
+use crate::user_settings::FlycamSettings;
+
+/// How many degrees per second a fully tilted stick turns the camera at the default sensitivity.
+const LOOK_RATE: f32 = 120.0;
+/// bevy_flycam keeps the camera from pitching past straight up or down.
+const MAX_PITCH: f32 = 1.54;
+
+/// Drives the [`FlyCam`] camera and the [`TunerCamera`]'s bloom settings and FOV with any connected
+/// gamepad. Speed and sensitivity follow the flycam's [`MovementSettings`], so bevy_flycam's
+/// plugin is required, and [`BloomTunerPlugin`](crate::BloomTunerPlugin) for the tuned camera.
+pub struct GamepadPlugin;
+
+impl Plugin for GamepadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GamepadMenu>()
+            .add_systems(Update, (fly_with_gamepad, handle_gamepad_menu));
+    }
+}
+
+/// The parameter the D-pad changes.
+#[derive(Resource, Default, Debug)]
+pub struct GamepadMenu {
+    index: usize,
+}
+
+impl GamepadMenu {
+    pub fn current(&self) -> TunerParameter {
+        TunerParameter::ALL[self.index]
+    }
+
+    pub fn next(&mut self) {
+        self.index = (self.index + 1) % TunerParameter::ALL.len();
+    }
+
+    pub fn previous(&mut self) {
+        self.index = (self.index + TunerParameter::ALL.len() - 1) % TunerParameter::ALL.len();
+    }
+}
+
+/// The sum of an axis over every connected gamepad.
+fn axis_value<T: Copy + Eq + std::hash::Hash + Send + Sync + 'static>(
+    gamepads: &Gamepads,
+    axis: &Axis<T>,
+    input: impl Fn(Gamepad) -> T,
+) -> f32 {
+    gamepads
+        .iter()
+        .filter_map(|gamepad| axis.get(input(gamepad)))
+        .sum()
+}
+
+// ------------------------------------------------------------------------------------------------
+
+fn fly_with_gamepad(
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    button_axes: Res<Axis<GamepadButton>>,
+    time: Res<Time>,
+    settings: Res<MovementSettings>,
+    mut cameras: Query<&mut Transform, With<FlyCam>>,
+) {
+    let stick = |axis_type| axis_value(&gamepads, &axes, |g| GamepadAxis::new(g, axis_type));
+    let trigger = |button_type| {
+        axis_value(&gamepads, &button_axes, |g| {
+            GamepadButton::new(g, button_type)
+        })
+    };
+
+    let movement = Vec3::new(
+        stick(GamepadAxisType::LeftStickX),
+        trigger(GamepadButtonType::RightTrigger2) - trigger(GamepadButtonType::LeftTrigger2),
+        stick(GamepadAxisType::LeftStickY),
+    )
+    .clamp_length_max(1.0);
+    let look = Vec2::new(
+        stick(GamepadAxisType::RightStickX),
+        stick(GamepadAxisType::RightStickY),
+    )
+    .clamp_length_max(1.0);
+
+    if movement == Vec3::ZERO && look == Vec2::ZERO {
+        return;
+    }
+
+    let sensitivity = settings.sensitivity / FlycamSettings::default().sensitivity;
+    let dt = time.delta_seconds();
+
+    for mut transform in cameras.iter_mut() {
+        // Move along the ground like bevy_flycam does, whichever way the camera is pitched.
+        let local_z = transform.local_z();
+        let forward = -Vec3::new(local_z.x, 0.0, local_z.z).normalize_or_zero();
+        let right = Vec3::new(local_z.z, 0.0, -local_z.x).normalize_or_zero();
+        let velocity = right * movement.x + Vec3::Y * movement.y + forward * movement.z;
+        transform.translation += velocity * settings.speed * dt;
+
+        let (mut yaw, mut pitch, _) = transform.rotation.to_euler(EulerRot::YXZ);
+        yaw -= (look.x * LOOK_RATE * sensitivity * dt).to_radians();
+        pitch += (look.y * LOOK_RATE * sensitivity * dt).to_radians();
+        pitch = pitch.clamp(-MAX_PITCH, MAX_PITCH);
+        transform.rotation =
+            Quat::from_axis_angle(Vec3::Y, yaw) * Quat::from_axis_angle(Vec3::X, pitch);
+    }
+}
+
+/// D-pad up and down select a parameter, left and right change it, speeding up the longer they
+/// are held.
+fn handle_gamepad_menu(
+    gamepads: Res<Gamepads>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+    time: Res<Time>,
+    mut held_for: Local<f32>,
+    mut menu: ResMut<GamepadMenu>,
+    mut camera: Query<(Option<&mut BloomSettings>, &mut Projection), With<TunerCamera>>,
+) {
+    let pressed = |button_type| {
+        gamepads
+            .iter()
+            .any(|gamepad| buttons.pressed(GamepadButton::new(gamepad, button_type)))
+    };
+    let just_pressed = |button_type| {
+        gamepads
+            .iter()
+            .any(|gamepad| buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+    };
+
+    if just_pressed(GamepadButtonType::DPadUp) {
+        menu.previous();
+    }
+    if just_pressed(GamepadButtonType::DPadDown) {
+        menu.next();
+    }
+
+    let direction = match (
+        pressed(GamepadButtonType::DPadRight),
+        pressed(GamepadButtonType::DPadLeft),
+    ) {
+        (true, false) => 1.0,
+        (false, true) => -1.0,
+        _ => {
+            *held_for = 0.0;
+            return;
+        }
+    };
+    *held_for += time.delta_seconds();
+
+    let Ok((mut bloom_settings, mut projection)) = camera.get_single_mut() else {
+        return;
+    };
+
+    // The FOV can be adjusted while bloom is off, the bloom parameters only while it is on.
+    let parameter = menu.current();
+    let change = direction
+        * hold_multiplier(*held_for)
+        * parameter.adjust_rate(&projection)
+        * time.delta_seconds();
+    parameter.adjust(bloom_settings.as_deref_mut(), &mut projection, change);
+}
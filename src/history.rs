@@ -0,0 +1,293 @@
+//! Undo and redo for every change made to the [`TunerCamera`]'s bloom, tonemapping and projection.
+
+use bevy::core_pipeline::{bloom::BloomSettings, tonemapping::Tonemapping};
+use bevy::prelude::*;
+
+use crate::bindings::{TunerAction, TunerBindings};
+use crate::tuner::TunerCamera;
+
+/// How many edits are kept before the oldest is forgotten.
+const MAX_HISTORY: usize = 100;
+
+/// The tunable state of the [`TunerCamera`].
+#[derive(Clone)]
+pub struct TunerSnapshot {
+    /// The camera this state belongs to, since the tuner can be moved between cameras.
+    pub camera: Entity,
+    pub bloom_settings: Option<BloomSettings>,
+    pub tonemapping: Tonemapping,
+    pub projection: Projection,
+}
+
+impl TunerSnapshot {
+    fn same_as(&self, other: &Self) -> bool {
+        let bloom_eq = match (&self.bloom_settings, &other.bloom_settings) {
+            (Some(a), Some(b)) => a.reflect_partial_eq(b).unwrap_or(false),
+            (None, None) => true,
+            _ => false,
+        };
+
+        self.camera == other.camera
+            && bloom_eq
+            && self.tonemapping == other.tonemapping
+            && self
+                .projection
+                .reflect_partial_eq(&other.projection)
+                .unwrap_or(false)
+    }
+}
+
+/// A single undoable change, from one snapshot to another.
+pub struct TunerEdit {
+    pub before: TunerSnapshot,
+    pub after: TunerSnapshot,
+}
+
+/// Every edit made through the tuner. Edits before `position` are applied, edits after it have
+/// been undone and can be redone.
+#[derive(Resource, Default)]
+pub struct TunerHistory {
+    edits: Vec<TunerEdit>,
+    position: usize,
+    /// The state at the end of the last recorded edit, which the next edit starts from.
+    committed: Option<TunerSnapshot>,
+}
+
+impl TunerHistory {
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn len(&self) -> usize {
+        self.edits.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    fn record(&mut self, after: TunerSnapshot) {
+        // The first state seen, or the tuner moving to another camera, is a new starting point
+        // rather than an edit.
+        let Some(before) = self
+            .committed
+            .replace(after.clone())
+            .filter(|before| before.camera == after.camera)
+        else {
+            return;
+        };
+
+        self.edits.truncate(self.position);
+        self.edits.push(TunerEdit { before, after });
+
+        if self.edits.len() > MAX_HISTORY {
+            self.edits.remove(0);
+        }
+        self.position = self.edits.len();
+    }
+
+    fn undo(&mut self) -> Option<TunerSnapshot> {
+        self.position = self.position.checked_sub(1)?;
+
+        let snapshot = self.edits[self.position].before.clone();
+        self.committed = Some(snapshot.clone());
+
+        Some(snapshot)
+    }
+
+    fn redo(&mut self) -> Option<TunerSnapshot> {
+        let snapshot = self.edits.get(self.position)?.after.clone();
+        self.position += 1;
+        self.committed = Some(snapshot.clone());
+
+        Some(snapshot)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+type CameraStateQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Option<&'static mut BloomSettings>,
+        &'static mut Tonemapping,
+        &'static mut Projection,
+    ),
+    With<Camera>,
+>;
+
+/// Steps through the history with Ctrl and the undo and redo keys.
+pub(crate) fn undo_redo(
+    mut commands: Commands,
+    keycode: Res<ButtonInput<KeyCode>>,
+    bindings: Res<TunerBindings>,
+    mut history: ResMut<TunerHistory>,
+    mut cameras: CameraStateQuery,
+) {
+    let snapshot = if bindings.shortcut_just_pressed(&keycode, TunerAction::Undo) {
+        history.undo()
+    } else if bindings.shortcut_just_pressed(&keycode, TunerAction::Redo) {
+        history.redo()
+    } else {
+        None
+    };
+
+    let Some(snapshot) = snapshot else {
+        return;
+    };
+    let entity = snapshot.camera;
+    let Ok((bloom_settings, mut tonemapping, mut projection)) = cameras.get_mut(entity) else {
+        return;
+    };
+
+    match (snapshot.bloom_settings, bloom_settings) {
+        (Some(settings), Some(mut bloom_settings)) => *bloom_settings = settings,
+        (Some(settings), None) => {
+            commands.entity(entity).insert(settings);
+        }
+        (None, _) => {
+            commands.entity(entity).remove::<BloomSettings>();
+        }
+    }
+    *tonemapping = snapshot.tonemapping;
+    *projection = snapshot.projection;
+}
+
+/// Records an edit once the tuner state has changed and every tuner key, mouse button and gamepad
+/// button has been released, so holding a key down or dragging a slider becomes a single edit.
+pub(crate) fn record_history(
+    keycode: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    bindings: Res<TunerBindings>,
+    mut history: ResMut<TunerHistory>,
+    camera: Query<(Entity, Option<&BloomSettings>, &Tonemapping, &Projection), With<TunerCamera>>,
+) {
+    let Ok((entity, bloom_settings, tonemapping, projection)) = camera.get_single() else {
+        return;
+    };
+
+    if bindings.0.values().any(|&key| keycode.pressed(key))
+        || mouse.get_pressed().next().is_some()
+        || gamepad_buttons.get_pressed().next().is_some()
+    {
+        return;
+    }
+
+    let snapshot = TunerSnapshot {
+        camera: entity,
+        bloom_settings: bloom_settings.cloned(),
+        tonemapping: *tonemapping,
+        projection: projection.clone(),
+    };
+
+    if !history
+        .committed
+        .as_ref()
+        .is_some_and(|committed| committed.same_as(&snapshot))
+    {
+        history.record(snapshot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A snapshot of `camera` told apart by its bloom intensity.
+    fn snapshot(camera: u32, intensity: f32) -> TunerSnapshot {
+        TunerSnapshot {
+            camera: Entity::from_raw(camera),
+            bloom_settings: Some(BloomSettings {
+                intensity,
+                ..BloomSettings::NATURAL
+            }),
+            tonemapping: Tonemapping::default(),
+            projection: Projection::default(),
+        }
+    }
+
+    fn intensity(snapshot: Option<TunerSnapshot>) -> Option<f32> {
+        snapshot.and_then(|snapshot| snapshot.bloom_settings.map(|bloom| bloom.intensity))
+    }
+
+    #[test]
+    fn first_snapshot_is_not_an_edit() {
+        let mut history = TunerHistory::default();
+        history.record(snapshot(0, 0.0));
+
+        assert!(history.is_empty());
+        assert_eq!(intensity(history.undo()), None);
+    }
+
+    #[test]
+    fn undo_and_redo_stop_at_the_ends() {
+        let mut history = TunerHistory::default();
+        history.record(snapshot(0, 0.0));
+        history.record(snapshot(0, 0.1));
+        history.record(snapshot(0, 0.2));
+
+        assert_eq!(intensity(history.redo()), None);
+        assert_eq!(intensity(history.undo()), Some(0.1));
+        assert_eq!(intensity(history.undo()), Some(0.0));
+        assert_eq!(intensity(history.undo()), None);
+        assert_eq!(history.position(), 0);
+
+        assert_eq!(intensity(history.redo()), Some(0.1));
+        assert_eq!(intensity(history.redo()), Some(0.2));
+        assert_eq!(intensity(history.redo()), None);
+        assert_eq!(history.position(), 2);
+    }
+
+    #[test]
+    fn edit_after_undo_drops_redo_tail() {
+        let mut history = TunerHistory::default();
+        history.record(snapshot(0, 0.0));
+        history.record(snapshot(0, 0.1));
+        history.record(snapshot(0, 0.2));
+
+        history.undo();
+        history.record(snapshot(0, 0.3));
+
+        assert_eq!(history.len(), 2);
+        assert_eq!(intensity(history.redo()), None);
+        // The new edit starts from the state the undo went back to.
+        assert_eq!(intensity(history.undo()), Some(0.1));
+        assert_eq!(intensity(history.undo()), Some(0.0));
+    }
+
+    #[test]
+    fn oldest_edit_is_dropped_past_max_history() {
+        let mut history = TunerHistory::default();
+        for i in 0..=MAX_HISTORY + 1 {
+            history.record(snapshot(0, i as f32));
+        }
+
+        assert_eq!(history.len(), MAX_HISTORY);
+        assert_eq!(history.position(), MAX_HISTORY);
+
+        let mut oldest = None;
+        while let Some(before) = history.undo() {
+            oldest = Some(before);
+        }
+        // The edit from 0 to 1 was forgotten, so undoing stops at 1.
+        assert_eq!(intensity(oldest), Some(1.0));
+    }
+
+    #[test]
+    fn moving_to_another_camera_starts_again() {
+        let mut history = TunerHistory::default();
+        history.record(snapshot(0, 0.0));
+        history.record(snapshot(0, 0.1));
+        history.record(snapshot(1, 0.5));
+
+        assert_eq!(history.len(), 1);
+
+        history.record(snapshot(1, 0.6));
+        assert_eq!(history.len(), 2);
+        let before = history.undo().unwrap();
+        assert_eq!(before.camera, Entity::from_raw(1));
+        assert_eq!(intensity(Some(before)), Some(0.5));
+    }
+}
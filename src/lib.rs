@@ -0,0 +1,33 @@
+//! A keyboard driven bloom tuner and a grid of bouncing spheres to try it on, packaged as plugins
+//! which can be added to any Bevy app.
+
+pub mod bindings;
+pub mod comparison;
+pub mod conflicts;
+pub mod diagnostics;
+pub mod environment;
+pub mod flycam;
+pub mod gamepad;
+pub mod history;
+pub mod lights;
+pub mod material_editor;
+pub mod motion;
+pub mod panel;
+pub mod parameters;
+pub mod physics;
+pub mod presets;
+pub mod rebinding;
+pub mod selection;
+pub mod sphere_grid;
+pub mod tuner;
+pub mod user_settings;
+
+pub use diagnostics::DiagnosticsOverlayPlugin;
+pub use environment::EnvironmentPlugin;
+pub use flycam::FlycamControlPlugin;
+pub use gamepad::GamepadPlugin;
+pub use panel::TunerPanelPlugin;
+pub use rebinding::BindingsScreenPlugin;
+pub use selection::SelectionPlugin;
+pub use sphere_grid::SphereGridPlugin;
+pub use tuner::{BloomTunerPlugin, TunerCamera};
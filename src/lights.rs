@@ -0,0 +1,87 @@
+//! Point lights carried by the emissive grid spheres, so their glow lights up their neighbours.
+
+use bevy::prelude::*;
+
+use crate::sphere_grid::SphereGridConfig;
+use crate::tuner::TunerCamera;
+
+/// How emissive spheres light the scene. Set through
+/// [`SphereGridPlugin::with_lights`](crate::SphereGridPlugin::with_lights).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SphereLightSettings {
+    /// Lumens of light per unit of the brightest emissive channel.
+    pub intensity_scale: f32,
+    pub range: f32,
+    /// How many lights are on at once. The lights closest to the camera are kept on.
+    pub max_active: usize,
+}
+
+impl Default for SphereLightSettings {
+    fn default() -> Self {
+        Self {
+            intensity_scale: 20.0,
+            range: 10.0,
+            max_active: 32,
+        }
+    }
+}
+
+impl SphereLightSettings {
+    /// A light matching the color of `emission`, as bright as its brightest channel allows.
+    pub fn light_for(&self, emission: Color) -> PointLight {
+        let [r, g, b, _] = emission.as_linear_rgba_f32();
+        let brightest = r.max(g).max(b);
+
+        PointLight {
+            color: if brightest > 0.0 {
+                Color::rgb_linear(r / brightest, g / brightest, b / brightest)
+            } else {
+                Color::BLACK
+            },
+            intensity: brightest * self.intensity_scale,
+            range: self.range,
+            ..default()
+        }
+    }
+}
+
+/// A point light following an emissive grid sphere, as a child of it.
+#[derive(Component)]
+pub struct SphereLight;
+
+// ------------------------------------------------------------------------------------------------
+
+/// Switches on the [`SphereLight`]s closest to the [`TunerCamera`], up to the cap, and switches off
+/// the rest.
+pub(crate) fn limit_sphere_lights(
+    camera: Query<&GlobalTransform, With<TunerCamera>>,
+    mut lights: Query<(Entity, &GlobalTransform, &mut Visibility), With<SphereLight>>,
+    config: Res<SphereGridConfig>,
+) {
+    let (Some(settings), Some(camera)) = (config.lights, camera.get_single().ok()) else {
+        return;
+    };
+    let camera = camera.translation();
+
+    let mut by_distance: Vec<(Entity, f32)> = lights
+        .iter()
+        .map(|(entity, transform, _)| (entity, transform.translation().distance_squared(camera)))
+        .collect();
+    by_distance.sort_unstable_by(|(_, a), (_, b)| a.total_cmp(b));
+
+    for (index, (entity, _)) in by_distance.into_iter().enumerate() {
+        let Ok((_, _, mut visibility)) = lights.get_mut(entity) else {
+            continue;
+        };
+
+        let wanted = if index < settings.max_active {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        // Only write when it changes, so the lights are not flagged as changed every frame.
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+    }
+}
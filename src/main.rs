@@ -1,46 +1,238 @@
 //! This example demonstrates how to use the `Camera::viewport_to_world` method.
 
-use bevy::input::keyboard::KeyboardInput;
+use application::lights::SphereLightSettings;
+use application::motion::{BounceMotion, MotionPattern};
+use application::physics::PhysicsSettings;
+use application::{
+    BindingsScreenPlugin, BloomTunerPlugin, DiagnosticsOverlayPlugin, EnvironmentPlugin,
+    FlycamControlPlugin, GamepadPlugin, SelectionPlugin, SphereGridPlugin, TunerCamera,
+    TunerPanelPlugin,
+};
+use bevy::core_pipeline::{bloom::BloomSettings, tonemapping::Tonemapping};
 use bevy::prelude::*;
 use bevy_flycam::prelude::*;
+use clap::Parser;
+use std::str::FromStr;
 
-const INITIAL_FOV: f32 = 75f32;
+#[derive(Parser)]
+#[command(about = "Bloom tuning playground")]
+struct Args {
+    /// Number of spheres along x and z, e.g. 20x20 [default: 10x10, or 200x200 with --stress]
+    #[arg(long, value_parser = parse_pair::<u32>)]
+    grid: Option<(u32, u32)>,
 
-use bevy::{
-    core_pipeline::{
-        bloom::{BloomCompositeMode, BloomSettings},
-        tonemapping::Tonemapping,
-    },
-    prelude::*,
-};
-use std::{
-    collections::hash_map::DefaultHasher,
-    hash::{Hash, Hasher},
-};
+    /// Stress test with a large grid of low-poly spheres, to find where animation, physics or
+    /// rendering becomes the bottleneck
+    #[arg(long)]
+    stress: bool,
+
+    /// Distance between neighbouring spheres
+    #[arg(long, default_value_t = 2.0)]
+    spacing: f32,
+
+    /// Initial vertical field of view, in degrees
+    #[arg(long, default_value_t = 75.0)]
+    fov: f32,
+
+    /// Seed for the random sphere materials; the same seed always gives the same layout
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    /// How the spheres bounce: diagonal, radial, x-wave, z-wave, noise or standing
+    #[arg(long, default_value = "diagonal")]
+    motion: MotionPattern,
+
+    /// Height of the sphere bounce
+    #[arg(long, default_value_t = 1.0)]
+    amplitude: f32,
+
+    /// How tightly packed the bounce waves are
+    #[arg(long, default_value_t = 1.0)]
+    frequency: f32,
+
+    /// How fast the spheres bounce
+    #[arg(long, default_value_t = 1.0)]
+    bounce_speed: f32,
+
+    /// Give each emissive sphere a point light matching its color
+    #[arg(long)]
+    lights: bool,
+
+    /// Lumens of sphere light per unit of emission
+    #[arg(long, default_value_t = 20.0)]
+    light_scale: f32,
+
+    /// Most sphere lights switched on at once, nearest the camera first
+    #[arg(long, default_value_t = 32)]
+    max_lights: usize,
+
+    /// Lay a ground plane under the grid
+    #[arg(long)]
+    ground: bool,
+
+    /// Show the skybox and light the scene with its environment map. Needs --environment-maps
+    #[arg(long, requires = "environment_maps")]
+    skybox: bool,
+
+    /// Diffuse and specular KTX2 cubemaps for the skybox, relative to the assets folder. None ship
+    /// with the app; pisa_diffuse_rgb9e5_zstd.ktx2 and pisa_specular_rgb9e5_zstd.ktx2 from
+    /// assets/environment_maps in the Bevy repository work
+    #[arg(long, num_args = 2, value_names = ["DIFFUSE", "SPECULAR"])]
+    environment_maps: Option<Vec<String>>,
+
+    /// Ambient light brightness, in cd/m²
+    #[arg(long, default_value_t = 80.0)]
+    ambient: f32,
+
+    /// Simulate the spheres falling and colliding instead of bouncing them. Works with --stress,
+    /// as each sphere is only tested against its neighbours
+    #[arg(long)]
+    physics: bool,
+
+    /// Bloom preset from assets/presets to start with, e.g. warm.ron
+    #[arg(long)]
+    preset: Option<String>,
+
+    /// Window size in logical pixels, e.g. 1920x1080
+    #[arg(long, value_parser = parse_pair::<f32>, default_value = "1280x720")]
+    window: (f32, f32),
+
+    /// Flycam mouse sensitivity [default: saved setting, or 0.00015]
+    #[arg(long)]
+    sensitivity: Option<f32>,
+
+    /// Flycam movement speed [default: saved setting, or 12]
+    #[arg(long)]
+    speed: Option<f32>,
+
+    /// Camera start position, e.g. -2,2.5,5
+    #[arg(long, value_parser = parse_vec3, default_value = "-2,2.5,5", allow_hyphen_values = true)]
+    camera: Vec3,
+
+    /// Point the camera starts looking at, e.g. 0,0,0
+    #[arg(long, value_parser = parse_vec3, default_value = "0,0,0", allow_hyphen_values = true)]
+    look_at: Vec3,
+}
+
+/// Parses a `<a>x<b>` pair such as `20x20`.
+fn parse_pair<T: FromStr>(s: &str) -> Result<(T, T), String> {
+    let (a, b) = s
+        .split_once('x')
+        .ok_or_else(|| format!("expected <a>x<b>, got `{s}`"))?;
+    let parse = |v: &str| {
+        v.trim()
+            .parse()
+            .map_err(|_| format!("invalid number `{v}`"))
+    };
+
+    Ok((parse(a)?, parse(b)?))
+}
+
+/// Parses an `x,y,z` vector such as `-2,2.5,5`.
+fn parse_vec3(s: &str) -> Result<Vec3, String> {
+    let values = s
+        .split(',')
+        .map(|v| {
+            v.trim()
+                .parse()
+                .map_err(|_| format!("invalid number `{v}`"))
+        })
+        .collect::<Result<Vec<f32>, _>>()?;
+
+    match values[..] {
+        [x, y, z] => Ok(Vec3::new(x, y, z)),
+        _ => Err(format!("expected x,y,z, got `{s}`")),
+    }
+}
+
+/// Where the camera is spawned and how it is set up.
+#[derive(Resource)]
+struct CameraStart {
+    transform: Transform,
+    fov: f32,
+}
 
 fn main() {
+    let args = Args::parse();
+
+    let default_grid = if args.stress { (200, 200) } else { (10, 10) };
+    let (grid_x, grid_z) = args.grid.unwrap_or(default_grid);
+
+    let mut sphere_grid = SphereGridPlugin::new()
+        .with_size(grid_x, grid_z)
+        .with_spacing(args.spacing)
+        .with_seed(args.seed)
+        .with_motion(BounceMotion {
+            pattern: args.motion,
+            amplitude: args.amplitude,
+            frequency: args.frequency,
+            speed: args.bounce_speed,
+            ..default()
+        });
+    if args.stress {
+        sphere_grid = sphere_grid.with_subdivisions(1);
+    }
+    if args.lights {
+        sphere_grid = sphere_grid.with_lights(SphereLightSettings {
+            intensity_scale: args.light_scale,
+            max_active: args.max_lights,
+            ..default()
+        });
+    }
+    if args.physics {
+        sphere_grid = sphere_grid.with_physics(PhysicsSettings::default());
+    }
+
+    let mut flycam = FlycamControlPlugin::new();
+    if let Some(speed) = args.speed {
+        flycam = flycam.with_speed(speed);
+    }
+    if let Some(sensitivity) = args.sensitivity {
+        flycam = flycam.with_sensitivity(sensitivity);
+    }
+
+    let mut environment = EnvironmentPlugin::new()
+        .with_ground(args.ground)
+        .with_skybox(args.skybox)
+        .with_ambient_brightness(args.ambient);
+    if let Some([diffuse, specular]) = args.environment_maps.as_deref() {
+        environment = environment.with_environment_maps(diffuse, specular);
+    }
+
+    let mut tuner = BloomTunerPlugin::new();
+    if let Some(preset) = &args.preset {
+        tuner = tuner.with_startup_preset(preset);
+    }
+
     App::new()
-        .add_plugins(DefaultPlugins)
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                resolution: args.window.into(),
+                ..default()
+            }),
+            ..default()
+        }))
         .add_plugins(NoCameraPlayerPlugin)
-        .insert_resource(MovementSettings {
-            sensitivity: 0.00015, // default: 0.00012
-            speed: 12.0,          // default: 12.0
+        .add_plugins((
+            tuner,
+            sphere_grid,
+            SelectionPlugin,
+            DiagnosticsOverlayPlugin,
+            flycam,
+            BindingsScreenPlugin,
+            GamepadPlugin,
+            TunerPanelPlugin,
+            environment,
+        ))
+        .insert_resource(CameraStart {
+            transform: Transform::from_translation(args.camera).looking_at(args.look_at, Vec3::Y),
+            fov: args.fov,
         })
-        .insert_resource(KeyBindings {
-            move_ascend: KeyCode::KeyE,
-            move_descend: KeyCode::KeyQ,
-            ..Default::default()
-        })
-        .add_systems(Startup, setup_scene)
-        .add_systems(Update, (update_bloom_settings, bounce_spheres))
+        .add_systems(Startup, setup_camera)
         .run();
 }
 
-fn setup_scene(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-) {
+fn setup_camera(mut commands: Commands, start: Res<CameraStart>) {
     commands.spawn((
         Camera3dBundle {
             camera: Camera {
@@ -48,259 +240,17 @@ fn setup_scene(
                 ..default()
             },
             projection: PerspectiveProjection {
-                fov: INITIAL_FOV.to_radians(),
+                fov: start.fov.to_radians(),
                 ..default()
             }
             .into(),
             tonemapping: Tonemapping::TonyMcMapface, // 2. Using a tonemapper that desaturates to white is recommended
-            transform: Transform::from_xyz(-2.0, 2.5, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+            transform: start.transform,
             ..default()
         },
         // 3. Enable bloom for the camera
         BloomSettings::NATURAL,
         FlyCam,
+        TunerCamera,
     ));
-
-    let material_emissive1 = materials.add(StandardMaterial {
-        emissive: Color::rgb_linear(23000.0, 9000.0, 3000.0), // 4. Put something bright in a dark environment to see the effect
-        ..default()
-    });
-    let material_emissive2 = materials.add(StandardMaterial {
-        emissive: Color::rgb_linear(3000.0, 23000.0, 9000.0),
-        ..default()
-    });
-    let material_emissive3 = materials.add(StandardMaterial {
-        emissive: Color::rgb_linear(9000.0, 3000.0, 23000.0),
-        ..default()
-    });
-    let material_non_emissive = materials.add(StandardMaterial {
-        base_color: Color::GRAY,
-        ..default()
-    });
-
-    let mesh = meshes.add(Sphere::new(0.5).mesh().ico(5).unwrap());
-
-    for x in -5..5 {
-        for z in -5..5 {
-            // This generates a pseudo-random integer between `[0, 6)`, but deterministically so
-            // the same spheres are always the same colors.
-            let mut hasher = DefaultHasher::new();
-            (x, z).hash(&mut hasher);
-            let rand = (hasher.finish() - 2) % 6;
-
-            let material = match rand {
-                0 => material_emissive1.clone(),
-                1 => material_emissive2.clone(),
-                2 => material_emissive3.clone(),
-                3..=5 => material_non_emissive.clone(),
-                _ => unreachable!(),
-            };
-
-            commands.spawn((
-                PbrBundle {
-                    mesh: mesh.clone(),
-                    material,
-                    transform: Transform::from_xyz(x as f32 * 2.0, 0.0, z as f32 * 2.0),
-                    ..default()
-                },
-                Bouncing,
-            ));
-        }
-    }
-
-    // example instructions
-    commands.spawn(
-        TextBundle::from_section(
-            "",
-            TextStyle {
-                font_size: 20.0,
-                color: Color::WHITE,
-                ..default()
-            },
-        )
-        .with_style(Style {
-            position_type: PositionType::Absolute,
-            bottom: Val::Px(12.0),
-            left: Val::Px(12.0),
-            ..default()
-        }),
-    );
-}
-
-// ------------------------------------------------------------------------------------------------
-
-fn update_bloom_settings(
-    mut camera: Query<(Entity, Option<&mut BloomSettings>), With<Camera>>,
-    mut text: Query<&mut Text>,
-    mut commands: Commands,
-    keycode: Res<ButtonInput<KeyCode>>,
-    time: Res<Time>,
-    mut key_evr: EventReader<KeyboardInput>,
-    mut proj_query: Query<&mut Projection, With<FlyCam>>,
-) {
-    use bevy::input::ButtonState;
-
-    let bloom_settings = camera.single_mut();
-    let mut text = text.single_mut();
-    let text = &mut text.sections[0].value;
-
-    // assume perspective. do nothing if orthographic.
-    let Projection::Perspective(persp) = proj_query.single_mut().into_inner() else {
-        return;
-    };
-
-    match bloom_settings {
-        (entity, Some(mut bloom_settings)) => {
-            *text = "BloomSettings (Toggle: Space)\n".to_string();
-            text.push_str(&format!("(P/;) Intensity: {}\n", bloom_settings.intensity));
-            text.push_str(&format!(
-                "(O/L) Low-frequency boost: {}\n",
-                bloom_settings.low_frequency_boost
-            ));
-            text.push_str(&format!(
-                "(I/K) Low-frequency boost curvature: {}\n",
-                bloom_settings.low_frequency_boost_curvature
-            ));
-            text.push_str(&format!(
-                "(U/J) High-pass frequency: {}\n",
-                bloom_settings.high_pass_frequency
-            ));
-            text.push_str(&format!(
-                "(Y/H) Mode: {}\n",
-                match bloom_settings.composite_mode {
-                    BloomCompositeMode::EnergyConserving => "Energy-conserving",
-                    BloomCompositeMode::Additive => "Additive",
-                }
-            ));
-            text.push_str(&format!(
-                "(T/G) Threshold: {}\n",
-                bloom_settings.prefilter_settings.threshold
-            ));
-            text.push_str(&format!(
-                "(R/F) Threshold softness: {}\n",
-                bloom_settings.prefilter_settings.threshold_softness
-            ));
-            text.push_str(&format!("([/]) FOV: {}\n", persp.fov.to_degrees()));
-
-            let increase = 2f32.to_radians();
-
-            let dt = time.delta_seconds();
-
-            for ev in key_evr.read() {
-                match ev.state {
-                    ButtonState::Pressed => {
-                        match ev.key_code {
-                            KeyCode::BracketLeft | KeyCode::BracketRight => {
-                                persp.fov += increase
-                                    * if ev.key_code == KeyCode::BracketLeft {
-                                        1f32
-                                    } else {
-                                        -1f32
-                                    };
-
-                                persp.fov = persp.fov.clamp(0f32, 180f32.to_radians())
-                            }
-                            KeyCode::KeyP | KeyCode::Semicolon => {
-                                bloom_settings.intensity += dt / 10f32
-                                    * if ev.key_code == KeyCode::KeyP {
-                                        1f32
-                                    } else {
-                                        -1f32
-                                    };
-
-                                bloom_settings.intensity = bloom_settings.intensity.clamp(0.0, 1.0);
-                            }
-                            KeyCode::KeyO | KeyCode::KeyL => {
-                                bloom_settings.low_frequency_boost += dt / 10f32
-                                    * if ev.key_code == KeyCode::KeyO {
-                                        1f32
-                                    } else {
-                                        -1f32
-                                    };
-                            }
-                            KeyCode::KeyI | KeyCode::KeyK => {
-                                bloom_settings.low_frequency_boost_curvature += dt / 10f32
-                                    * if ev.key_code == KeyCode::KeyI {
-                                        1f32
-                                    } else {
-                                        -1f32
-                                    };
-
-                                bloom_settings.low_frequency_boost_curvature =
-                                    bloom_settings.low_frequency_boost_curvature.clamp(0.0, 1.0);
-                            }
-                            KeyCode::KeyU | KeyCode::KeyJ => {
-                                bloom_settings.high_pass_frequency += dt / 10f32
-                                    * if ev.key_code == KeyCode::KeyU {
-                                        1f32
-                                    } else {
-                                        -1f32
-                                    };
-
-                                bloom_settings.high_pass_frequency =
-                                    bloom_settings.high_pass_frequency.clamp(0.0, 1.0);
-                            }
-                            KeyCode::KeyY | KeyCode::KeyH => {
-                                bloom_settings.composite_mode = if ev.key_code == KeyCode::KeyY {
-                                    BloomCompositeMode::Additive
-                                } else {
-                                    BloomCompositeMode::EnergyConserving
-                                };
-                            }
-                            KeyCode::KeyT | KeyCode::KeyG => {
-                                bloom_settings.prefilter_settings.threshold += dt / 10f32
-                                    * if ev.key_code == KeyCode::KeyT {
-                                        1f32
-                                    } else {
-                                        -1f32
-                                    };
-
-                                bloom_settings.prefilter_settings.threshold =
-                                    bloom_settings.prefilter_settings.threshold.max(0.0);
-                            }
-                            KeyCode::KeyR | KeyCode::KeyF => {
-                                bloom_settings.prefilter_settings.threshold_softness += dt / 10f32
-                                    * if ev.key_code == KeyCode::KeyR {
-                                        1f32
-                                    } else {
-                                        -1f32
-                                    };
-
-                                bloom_settings.prefilter_settings.threshold_softness =
-                                    bloom_settings
-                                        .prefilter_settings
-                                        .threshold_softness
-                                        .clamp(0.0, 1.0);
-                            }
-                            KeyCode::Space => {
-                                commands.entity(entity).remove::<BloomSettings>();
-                            }
-                            _ => {}
-                        };
-                    }
-                    ButtonState::Released => {
-                        // println!("Key release: {:?} ({:?})", ev.key_code, ev.logical_key);
-                    }
-                }
-            }
-        }
-
-        (entity, None) => {
-            *text = "Bloom: Off (Toggle: Space)".to_string();
-
-            if keycode.just_pressed(KeyCode::Space) {
-                commands.entity(entity).insert(BloomSettings::NATURAL);
-            }
-        }
-    }
-}
-
-#[derive(Component)]
-struct Bouncing;
-
-fn bounce_spheres(time: Res<Time>, mut query: Query<&mut Transform, With<Bouncing>>) {
-    for mut transform in query.iter_mut() {
-        transform.translation.y =
-            (transform.translation.x + transform.translation.z + time.elapsed_seconds()).sin();
-    }
 }
@@ -0,0 +1,159 @@
+//! Editing the material of the selected grid sphere without affecting its neighbours.
+
+use bevy::prelude::*;
+
+use crate::bindings::{step_modifier, HeldActions, TunerAction, TunerBindings};
+use crate::selection::Selected;
+
+/// A value of a sphere's `StandardMaterial` which can be edited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaterialField {
+    EmissiveRed,
+    EmissiveGreen,
+    EmissiveBlue,
+    BaseRed,
+    BaseGreen,
+    BaseBlue,
+    Roughness,
+    Metallic,
+}
+
+impl MaterialField {
+    pub const ALL: [Self; 8] = [
+        Self::EmissiveRed,
+        Self::EmissiveGreen,
+        Self::EmissiveBlue,
+        Self::BaseRed,
+        Self::BaseGreen,
+        Self::BaseBlue,
+        Self::Roughness,
+        Self::Metallic,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::EmissiveRed => "Emissive red",
+            Self::EmissiveGreen => "Emissive green",
+            Self::EmissiveBlue => "Emissive blue",
+            Self::BaseRed => "Base red",
+            Self::BaseGreen => "Base green",
+            Self::BaseBlue => "Base blue",
+            Self::Roughness => "Roughness",
+            Self::Metallic => "Metallic",
+        }
+    }
+
+    /// How much a held key changes the field per second, before acceleration and modifiers.
+    /// Emissive channels are in the same linear HDR units as the grid's emissive materials.
+    fn adjust_rate(self) -> f32 {
+        match self {
+            Self::EmissiveRed | Self::EmissiveGreen | Self::EmissiveBlue => 5000.0,
+            _ => 0.5,
+        }
+    }
+
+    pub fn get(self, material: &StandardMaterial) -> f32 {
+        let emissive = material.emissive.as_linear_rgba_f32();
+        let base = material.base_color.as_rgba_f32();
+
+        match self {
+            Self::EmissiveRed => emissive[0],
+            Self::EmissiveGreen => emissive[1],
+            Self::EmissiveBlue => emissive[2],
+            Self::BaseRed => base[0],
+            Self::BaseGreen => base[1],
+            Self::BaseBlue => base[2],
+            Self::Roughness => material.perceptual_roughness,
+            Self::Metallic => material.metallic,
+        }
+    }
+
+    pub fn set(self, material: &mut StandardMaterial, value: f32) {
+        let mut emissive = material.emissive.as_linear_rgba_f32();
+        let mut base = material.base_color.as_rgba_f32();
+
+        match self {
+            Self::EmissiveRed => emissive[0] = value.max(0.0),
+            Self::EmissiveGreen => emissive[1] = value.max(0.0),
+            Self::EmissiveBlue => emissive[2] = value.max(0.0),
+            Self::BaseRed => base[0] = value.clamp(0.0, 1.0),
+            Self::BaseGreen => base[1] = value.clamp(0.0, 1.0),
+            Self::BaseBlue => base[2] = value.clamp(0.0, 1.0),
+            Self::Roughness => material.perceptual_roughness = value.clamp(0.0, 1.0),
+            Self::Metallic => material.metallic = value.clamp(0.0, 1.0),
+        }
+
+        material.emissive = Color::rgba_linear_from_array(emissive);
+        material.base_color = Color::rgba_from_array(base);
+    }
+}
+
+/// The [`MaterialField`] the material editing keys currently change.
+#[derive(Resource, Default)]
+pub struct MaterialFieldSelection {
+    index: usize,
+}
+
+impl MaterialFieldSelection {
+    pub fn current(&self) -> MaterialField {
+        MaterialField::ALL[self.index]
+    }
+}
+
+/// Marks a grid sphere whose material is its own rather than shared with the rest of the grid.
+#[derive(Component)]
+pub struct OwnMaterial;
+
+// ------------------------------------------------------------------------------------------------
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub(crate) fn edit_selected_material(
+    mut commands: Commands,
+    keycode: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    bindings: Res<TunerBindings>,
+    mut held: Local<HeldActions>,
+    mut field_selection: ResMut<MaterialFieldSelection>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut selected: Query<(Entity, &mut Handle<StandardMaterial>, Has<OwnMaterial>), With<Selected>>,
+) {
+    use TunerAction::*;
+
+    held.update(&bindings, &keycode, time.delta_seconds());
+
+    let field_count = MaterialField::ALL.len();
+    if bindings.just_pressed(&keycode, NextMaterialField) {
+        field_selection.index = (field_selection.index + 1) % field_count;
+    } else if bindings.just_pressed(&keycode, PreviousMaterialField) {
+        field_selection.index = (field_selection.index + field_count - 1) % field_count;
+    }
+
+    let axis = held.axis(MaterialValueUp, MaterialValueDown);
+    if axis == 0.0 {
+        return;
+    }
+
+    let Ok((entity, mut handle, has_own_material)) = selected.get_single_mut() else {
+        return;
+    };
+
+    // The grid spheres share a handful of materials, so give this sphere a copy of its own before
+    // the first edit.
+    if !has_own_material {
+        let Some(material) = materials.get(handle.id()).cloned() else {
+            return;
+        };
+
+        *handle = materials.add(material);
+        commands.entity(entity).insert(OwnMaterial);
+    }
+
+    let Some(material) = materials.get_mut(handle.id()) else {
+        return;
+    };
+
+    let field = field_selection.current();
+    let dt = time.delta_seconds() * step_modifier(&keycode);
+    let value = field.get(material) + axis * field.adjust_rate() * dt;
+    field.set(material, value);
+}
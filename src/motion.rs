@@ -0,0 +1,153 @@
+//! The patterns the grid spheres bounce in.
+
+use bevy::prelude::*;
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MotionPattern {
+    /// A wave travelling diagonally across the grid.
+    DiagonalWave,
+    /// Rings spreading outwards from a point on the ground plane.
+    RadialRipple { center: Vec2 },
+    /// A wave travelling along a direction on the ground plane.
+    AxisWave { direction: Vec2 },
+    /// Smoothly varying random heights.
+    Noise,
+    /// A wave whose peaks stay in place and only rise and fall.
+    StandingWave,
+}
+
+impl MotionPattern {
+    /// Every pattern, in the order they are cycled through.
+    pub const ALL: [Self; 6] = [
+        Self::DiagonalWave,
+        Self::RadialRipple { center: Vec2::ZERO },
+        Self::AxisWave { direction: Vec2::X },
+        Self::AxisWave { direction: Vec2::Y },
+        Self::Noise,
+        Self::StandingWave,
+    ];
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|&pattern| pattern == self)
+            .map_or(0, |index| index + 1);
+
+        Self::ALL[index % Self::ALL.len()]
+    }
+}
+
+impl fmt::Display for MotionPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DiagonalWave => write!(f, "diagonal"),
+            Self::RadialRipple { .. } => write!(f, "radial"),
+            Self::AxisWave { direction } if *direction == Vec2::X => write!(f, "x-wave"),
+            Self::AxisWave { direction } if *direction == Vec2::Y => write!(f, "z-wave"),
+            Self::AxisWave { direction } => write!(f, "wave ({}, {})", direction.x, direction.y),
+            Self::Noise => write!(f, "noise"),
+            Self::StandingWave => write!(f, "standing"),
+        }
+    }
+}
+
+impl FromStr for MotionPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|pattern| pattern.to_string() == s)
+            .ok_or_else(|| {
+                let names = Self::ALL.map(|pattern| pattern.to_string()).join(", ");
+                format!("unknown motion `{s}`, expected one of: {names}")
+            })
+    }
+}
+
+/// How a [`Bouncing`](crate::sphere_grid::Bouncing) sphere moves up and down.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct BounceMotion {
+    pub pattern: MotionPattern,
+    /// Greatest distance from rest height.
+    pub amplitude: f32,
+    /// How tightly packed the waves are across the grid.
+    pub frequency: f32,
+    /// Offset into the motion, in radians.
+    pub phase: f32,
+    /// How quickly the motion plays.
+    pub speed: f32,
+}
+
+impl Default for BounceMotion {
+    fn default() -> Self {
+        Self {
+            pattern: MotionPattern::DiagonalWave,
+            amplitude: 1.0,
+            frequency: 1.0,
+            phase: 0.0,
+            speed: 1.0,
+        }
+    }
+}
+
+impl BounceMotion {
+    /// The height of a sphere resting at `position` on the ground plane, at time `t`.
+    pub fn height(&self, position: Vec2, t: f32) -> f32 {
+        let Self {
+            amplitude,
+            frequency,
+            phase,
+            speed,
+            ..
+        } = *self;
+        let time = t * speed + phase;
+
+        let wave = match self.pattern {
+            MotionPattern::DiagonalWave => (frequency * (position.x + position.y) + time).sin(),
+            MotionPattern::RadialRipple { center } => {
+                (frequency * position.distance(center) - time).sin()
+            }
+            MotionPattern::AxisWave { direction } => {
+                (frequency * position.dot(direction.normalize_or_zero()) + time).sin()
+            }
+            MotionPattern::Noise => value_noise((position * frequency).extend(time)) * 2.0 - 1.0,
+            MotionPattern::StandingWave => {
+                (frequency * position.x).sin() * (frequency * position.y).sin() * time.cos()
+            }
+        };
+
+        amplitude * wave
+    }
+}
+
+/// Smooth 3D value noise in `[0, 1]`.
+fn value_noise(point: Vec3) -> f32 {
+    let cell = point.floor();
+    let local = point - cell;
+    let smooth = local * local * (Vec3::splat(3.0) - 2.0 * local);
+    let cell = cell.as_ivec3();
+
+    let corner = |x, y, z| lattice_value(cell + IVec3::new(x, y, z));
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+
+    let x00 = lerp(corner(0, 0, 0), corner(1, 0, 0), smooth.x);
+    let x10 = lerp(corner(0, 1, 0), corner(1, 1, 0), smooth.x);
+    let x01 = lerp(corner(0, 0, 1), corner(1, 0, 1), smooth.x);
+    let x11 = lerp(corner(0, 1, 1), corner(1, 1, 1), smooth.x);
+
+    lerp(lerp(x00, x10, smooth.y), lerp(x01, x11, smooth.y), smooth.z)
+}
+
+/// A fixed pseudo-random value in `[0, 1]` for each lattice point.
+fn lattice_value(point: IVec3) -> f32 {
+    let mut hash = (point.x as u32).wrapping_mul(0x8da6_b343)
+        ^ (point.y as u32).wrapping_mul(0xd816_3841)
+        ^ (point.z as u32).wrapping_mul(0xcb1a_b31f);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0x5bd1_e995);
+    hash ^= hash >> 15;
+
+    hash as f32 / u32::MAX as f32
+}
@@ -0,0 +1,552 @@
+//! A bevy_ui panel for tuning with the mouse, with a slider, readout and reset button for each
+//! [`TunerParameter`], a dropdown for the composite mode and a button toggling bloom. Clicking a
+//! readout lets an exact value be typed in.
+
+use bevy::core_pipeline::bloom::{BloomCompositeMode, BloomSettings};
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::ui::{FocusPolicy, RelativeCursorPosition};
+use bevy::window::ReceivedCharacter;
+use std::mem;
+
+use crate::bindings::{KeyboardLayout, TunerAction, TunerBindings};
+use crate::parameters::TunerParameter;
+use crate::rebinding::{handle_bindings_screen, BindingsScreen};
+use crate::tuner::{composite_mode_name, TunerCamera, TunerConfig};
+
+const FONT_SIZE: f32 = 16.0;
+const NAME_WIDTH: f32 = 220.0;
+const SLIDER_WIDTH: f32 = 140.0;
+const READOUT_WIDTH: f32 = 70.0;
+
+const PANEL_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+const BUTTON_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
+const HOVERED_BUTTON_COLOR: Color = Color::rgb(0.25, 0.25, 0.25);
+const PRESSED_BUTTON_COLOR: Color = Color::rgb(0.35, 0.35, 0.35);
+const SLIDER_FILL_COLOR: Color = Color::rgb(0.9, 0.6, 0.2);
+const EDITING_COLOR: Color = Color::rgb(0.2, 0.3, 0.5);
+
+const COMPOSITE_MODES: [BloomCompositeMode; 2] = [
+    BloomCompositeMode::EnergyConserving,
+    BloomCompositeMode::Additive,
+];
+
+/// Adds a panel on the right of the window for changing the [`TunerCamera`]'s bloom settings and
+/// FOV with the mouse, shown and hidden with the [`TunerAction::TogglePanel`] key. While a value is
+/// being typed it takes all keyboard input. Requires [`BloomTunerPlugin`](crate::BloomTunerPlugin).
+pub struct TunerPanelPlugin;
+
+impl Plugin for TunerPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TunerPanel>()
+            .add_systems(Startup, setup_panel)
+            .add_systems(
+                PreUpdate,
+                type_parameter_value
+                    .after(InputSystem)
+                    .after(handle_bindings_screen),
+            )
+            .add_systems(
+                Update,
+                (toggle_panel, handle_panel_buttons, update_panel).chain(),
+            );
+    }
+}
+
+/// The state of the panel.
+#[derive(Resource, Debug)]
+pub struct TunerPanel {
+    pub open: bool,
+    /// Whether the composite mode dropdown is showing its options.
+    pub composite_mode_menu_open: bool,
+    /// The parameter a value is being typed for.
+    pub editing: Option<TunerParameter>,
+    /// What has been typed so far.
+    pub entry: String,
+    /// The outcome of the last typed value, or how to enter one.
+    pub message: String,
+}
+
+impl Default for TunerPanel {
+    fn default() -> Self {
+        Self {
+            open: true,
+            composite_mode_menu_open: false,
+            editing: None,
+            entry: String::new(),
+            message: String::new(),
+        }
+    }
+}
+
+/// Marks the node holding the whole panel.
+#[derive(Component)]
+pub struct TunerPanelRoot;
+
+/// Marks the node holding the composite mode options.
+#[derive(Component)]
+pub struct CompositeModeList;
+
+/// Something in the panel which reacts to being clicked.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub enum PanelButton {
+    /// Sets the parameter to the position clicked or dragged to.
+    Slider(TunerParameter),
+    Reset(TunerParameter),
+    /// Starts typing a value for the parameter.
+    Edit(TunerParameter),
+    ToggleBloom,
+    /// Opens and closes the composite mode dropdown.
+    CompositeModeMenu,
+    CompositeMode(BloomCompositeMode),
+}
+
+/// A text in the panel showing part of the tuner state.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub enum PanelText {
+    Title,
+    Name(TunerParameter),
+    Readout(TunerParameter),
+    Bloom,
+    CompositeMode,
+    Message,
+}
+
+/// The filled part of a parameter's slider.
+#[derive(Component)]
+pub struct SliderFill(TunerParameter);
+
+/// The value a parameter is reset to, from the [`TunerConfig::default_settings`] or the projection
+/// the camera started with.
+fn default_value(
+    parameter: TunerParameter,
+    config: &TunerConfig,
+    initial_projection: &Projection,
+    projection: &Projection,
+) -> f32 {
+    // If the projection has been switched since, fall back to Bevy's defaults for the other kind.
+    let reference = if mem::discriminant(initial_projection) == mem::discriminant(projection) {
+        initial_projection.clone()
+    } else {
+        match projection {
+            Projection::Perspective(_) => Projection::Perspective(default()),
+            Projection::Orthographic(_) => Projection::Orthographic(default()),
+        }
+    };
+
+    // Every parameter has a value, as the default bloom settings are given.
+    parameter
+        .get(Some(&config.default_settings), &reference)
+        .unwrap_or_default()
+}
+
+/// Characters which can be part of a typed value.
+fn is_number_character(character: char) -> bool {
+    character.is_ascii_digit() || matches!(character, '.' | '-' | '+' | 'e' | 'E')
+}
+
+/// Sets `text` only when it differs, so unchanged text is not laid out again.
+fn set_text(text: &mut Text, value: String) {
+    if text.sections[0].value != value {
+        text.sections[0].value = value;
+    }
+}
+
+fn set_display(style: &mut Style, shown: bool) {
+    let display = if shown { Display::Flex } else { Display::None };
+    if style.display != display {
+        style.display = display;
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+fn setup_panel(mut commands: Commands, panel: Res<TunerPanel>) {
+    let text_style = TextStyle {
+        font_size: FONT_SIZE,
+        color: Color::WHITE,
+        ..default()
+    };
+    let text = |width: Option<f32>, panel_text: PanelText| {
+        (
+            TextBundle::from_section("", text_style.clone()).with_style(Style {
+                width: width.map_or(Val::Auto, Val::Px),
+                ..default()
+            }),
+            panel_text,
+        )
+    };
+    let label = |value: &str| TextBundle::from_section(value, text_style.clone());
+    let button = |width: Option<f32>, panel_button: PanelButton| {
+        (
+            ButtonBundle {
+                style: Style {
+                    width: width.map_or(Val::Auto, Val::Px),
+                    padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+                    ..default()
+                },
+                background_color: BUTTON_COLOR.into(),
+                ..default()
+            },
+            panel_button,
+        )
+    };
+    let row = || NodeBundle {
+        style: Style {
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(8.0),
+            ..default()
+        },
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(120.0),
+                    right: Val::Px(12.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(6.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    display: if panel.open {
+                        Display::Flex
+                    } else {
+                        Display::None
+                    },
+                    ..default()
+                },
+                background_color: PANEL_COLOR.into(),
+                // Clicks on the panel should not reach the scene behind it.
+                focus_policy: FocusPolicy::Block,
+                ..default()
+            },
+            Interaction::default(),
+            TunerPanelRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn(row()).with_children(|row| {
+                row.spawn(text(Some(NAME_WIDTH), PanelText::Title));
+                row.spawn(button(None, PanelButton::ToggleBloom))
+                    .with_children(|button| {
+                        button.spawn(text(None, PanelText::Bloom));
+                    });
+            });
+
+            for parameter in TunerParameter::ALL {
+                parent.spawn(row()).with_children(|row| {
+                    row.spawn(text(Some(NAME_WIDTH), PanelText::Name(parameter)));
+                    row.spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(SLIDER_WIDTH),
+                                height: Val::Px(12.0),
+                                ..default()
+                            },
+                            background_color: BUTTON_COLOR.into(),
+                            ..default()
+                        },
+                        RelativeCursorPosition::default(),
+                        PanelButton::Slider(parameter),
+                    ))
+                    .with_children(|track| {
+                        track.spawn((
+                            NodeBundle {
+                                style: Style {
+                                    width: Val::Percent(0.0),
+                                    height: Val::Percent(100.0),
+                                    ..default()
+                                },
+                                background_color: SLIDER_FILL_COLOR.into(),
+                                ..default()
+                            },
+                            SliderFill(parameter),
+                        ));
+                    });
+                    row.spawn(button(Some(READOUT_WIDTH), PanelButton::Edit(parameter)))
+                        .with_children(|button| {
+                            button.spawn(text(None, PanelText::Readout(parameter)));
+                        });
+                    row.spawn(button(None, PanelButton::Reset(parameter)))
+                        .with_children(|button| {
+                            button.spawn(label("Reset"));
+                        });
+                });
+            }
+
+            parent.spawn(row()).with_children(|row| {
+                row.spawn(label("Mode").with_style(Style {
+                    width: Val::Px(NAME_WIDTH),
+                    ..default()
+                }));
+                row.spawn(NodeBundle::default()).with_children(|dropdown| {
+                    dropdown
+                        .spawn(button(Some(SLIDER_WIDTH), PanelButton::CompositeModeMenu))
+                        .with_children(|button| {
+                            button.spawn(text(None, PanelText::CompositeMode));
+                        });
+                    // Shown below the dropdown button, over whatever is there.
+                    dropdown
+                        .spawn((
+                            NodeBundle {
+                                style: Style {
+                                    position_type: PositionType::Absolute,
+                                    top: Val::Percent(100.0),
+                                    flex_direction: FlexDirection::Column,
+                                    display: Display::None,
+                                    ..default()
+                                },
+                                z_index: ZIndex::Local(1),
+                                ..default()
+                            },
+                            CompositeModeList,
+                        ))
+                        .with_children(|list| {
+                            for mode in COMPOSITE_MODES {
+                                list.spawn(button(
+                                    Some(SLIDER_WIDTH),
+                                    PanelButton::CompositeMode(mode),
+                                ))
+                                .with_children(|button| {
+                                    button.spawn(label(composite_mode_name(mode)));
+                                });
+                            }
+                        });
+                });
+            });
+
+            parent.spawn(text(None, PanelText::Message));
+        });
+}
+
+fn toggle_panel(
+    keycode: Res<ButtonInput<KeyCode>>,
+    bindings: Res<TunerBindings>,
+    mut panel: ResMut<TunerPanel>,
+) {
+    if bindings.just_pressed(&keycode, TunerAction::TogglePanel) {
+        panel.open = !panel.open;
+        panel.composite_mode_menu_open = false;
+        panel.editing = None;
+    }
+}
+
+/// Collects the characters typed while a value is being entered, applying it on Enter and
+/// cancelling on Escape, or when the bindings screen opens. Runs straight after input is collected,
+/// and clears it while a value is being entered so no keys bound to other actions react.
+fn type_parameter_value(
+    mut keycode: ResMut<ButtonInput<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut panel: ResMut<TunerPanel>,
+    mut camera: Query<(Option<&mut BloomSettings>, &mut Projection), With<TunerCamera>>,
+    bindings_screen: Option<Res<BindingsScreen>>,
+) {
+    // The bindings screen takes all keyboard input while it is open.
+    if bindings_screen.is_some_and(|screen| screen.open) && panel.editing.is_some() {
+        panel.editing = None;
+        panel.message.clear();
+    }
+
+    let Some(parameter) = panel.editing else {
+        characters.clear();
+        return;
+    };
+
+    for event in characters.read() {
+        panel
+            .entry
+            .extend(event.char.chars().filter(|&c| is_number_character(c)));
+    }
+
+    if keycode.just_pressed(KeyCode::Backspace) {
+        panel.entry.pop();
+    }
+
+    if keycode.just_pressed(KeyCode::Escape) {
+        panel.editing = None;
+        panel.message.clear();
+    } else if keycode.any_just_pressed([KeyCode::Enter, KeyCode::NumpadEnter]) {
+        panel.editing = None;
+        panel.message = match camera.get_single_mut() {
+            Ok((mut bloom_settings, mut projection)) => {
+                let name = parameter.name(&projection);
+                match parameter.parse_value(&panel.entry, &projection) {
+                    Ok(value) => {
+                        if parameter.set(bloom_settings.as_deref_mut(), &mut projection, value) {
+                            format!("Set {name} to {value}")
+                        } else {
+                            format!("Rejected {name}, bloom is off")
+                        }
+                    }
+                    Err(err) => format!("Rejected {name}: {err}"),
+                }
+            }
+            Err(_) => "Rejected, there is no tuned camera".to_string(),
+        };
+    }
+
+    keycode.reset_all();
+}
+
+#[allow(clippy::type_complexity)]
+fn handle_panel_buttons(
+    mut commands: Commands,
+    clicked: Query<(&Interaction, &PanelButton), Changed<Interaction>>,
+    sliders: Query<(&Interaction, &PanelButton, &RelativeCursorPosition)>,
+    mut camera: Query<(Entity, Option<&mut BloomSettings>, &mut Projection), With<TunerCamera>>,
+    mut panel: ResMut<TunerPanel>,
+    mut initial_projection: Local<Option<Projection>>,
+    config: Res<TunerConfig>,
+) {
+    let Ok((entity, mut bloom_settings, mut projection)) = camera.get_single_mut() else {
+        return;
+    };
+    let initial_projection = initial_projection.get_or_insert_with(|| projection.clone());
+
+    for (interaction, &button) in &clicked {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            PanelButton::ToggleBloom => match bloom_settings {
+                Some(_) => {
+                    commands.entity(entity).remove::<BloomSettings>();
+                }
+                None => {
+                    commands
+                        .entity(entity)
+                        .insert(config.default_settings.clone());
+                }
+            },
+            PanelButton::CompositeModeMenu => {
+                panel.composite_mode_menu_open = !panel.composite_mode_menu_open;
+            }
+            PanelButton::CompositeMode(mode) => {
+                if let Some(bloom_settings) = bloom_settings.as_mut() {
+                    bloom_settings.composite_mode = mode;
+                }
+                panel.composite_mode_menu_open = false;
+            }
+            PanelButton::Reset(parameter) => {
+                let value = default_value(parameter, &config, initial_projection, &projection);
+                parameter.set(bloom_settings.as_deref_mut(), &mut projection, value);
+            }
+            PanelButton::Edit(parameter) => {
+                panel.editing = Some(parameter);
+                panel.entry.clear();
+                panel.message = format!(
+                    "Type a value for {}, Enter to apply, Esc to cancel",
+                    parameter.name(&projection)
+                );
+            }
+            PanelButton::Slider(_) => {}
+        }
+    }
+
+    // Sliders follow the cursor for as long as the mouse button is held, even outside of them.
+    // The bloom ones do nothing while bloom is off.
+    for (interaction, &button, cursor) in &sliders {
+        let (PanelButton::Slider(parameter), Interaction::Pressed, Some(cursor)) =
+            (button, interaction, cursor.normalized)
+        else {
+            continue;
+        };
+
+        let (min, max) = parameter.slider_range(&projection);
+        let value = min + cursor.x.clamp(0.0, 1.0) * (max - min);
+        parameter.set(bloom_settings.as_deref_mut(), &mut projection, value);
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_panel(
+    panel: Res<TunerPanel>,
+    mut root: Query<&mut Style, With<TunerPanelRoot>>,
+    mut list: Query<&mut Style, (With<CompositeModeList>, Without<TunerPanelRoot>)>,
+    mut fills: Query<
+        (&mut Style, &SliderFill),
+        (Without<TunerPanelRoot>, Without<CompositeModeList>),
+    >,
+    mut texts: Query<(&mut Text, &PanelText)>,
+    mut buttons: Query<(&Interaction, &PanelButton, &mut BackgroundColor)>,
+    camera: Query<(Option<&BloomSettings>, &Projection), With<TunerCamera>>,
+    bindings: Res<TunerBindings>,
+    layout: Res<KeyboardLayout>,
+) {
+    for mut style in root.iter_mut() {
+        set_display(&mut style, panel.open);
+    }
+    if !panel.open {
+        return;
+    }
+    for mut style in list.iter_mut() {
+        set_display(&mut style, panel.composite_mode_menu_open);
+    }
+
+    let Ok((bloom_settings, projection)) = camera.get_single() else {
+        return;
+    };
+
+    for (mut text, &panel_text) in texts.iter_mut() {
+        let value = match panel_text {
+            PanelText::Title => format!(
+                "Bloom settings (Toggle panel: {})",
+                bindings.label(TunerAction::TogglePanel, &layout)
+            ),
+            PanelText::Name(parameter) => parameter.name(projection).to_string(),
+            PanelText::Readout(parameter) if panel.editing == Some(parameter) => {
+                format!("{}_", panel.entry)
+            }
+            PanelText::Readout(parameter) => parameter
+                .get(bloom_settings, projection)
+                .map_or("-".to_string(), |value| format!("{value:.3}")),
+            PanelText::Bloom => {
+                format!(
+                    "Bloom: {}",
+                    if bloom_settings.is_some() {
+                        "On"
+                    } else {
+                        "Off"
+                    }
+                )
+            }
+            PanelText::CompositeMode => bloom_settings.map_or("-".to_string(), |bloom| {
+                composite_mode_name(bloom.composite_mode).to_string()
+            }),
+            PanelText::Message => panel.message.clone(),
+        };
+        set_text(&mut text, value);
+    }
+
+    for (mut style, SliderFill(parameter)) in fills.iter_mut() {
+        let fraction = parameter
+            .get(bloom_settings, projection)
+            .map_or(0.0, |value| {
+                let (min, max) = parameter.slider_range(projection);
+                ((value - min) / (max - min)).clamp(0.0, 1.0)
+            });
+        let width = Val::Percent(fraction * 100.0);
+        if style.width != width {
+            style.width = width;
+        }
+    }
+
+    for (interaction, &button, mut color) in buttons.iter_mut() {
+        let wanted = match interaction {
+            _ if panel
+                .editing
+                .is_some_and(|editing| button == PanelButton::Edit(editing)) =>
+            {
+                EDITING_COLOR
+            }
+            Interaction::Pressed => PRESSED_BUTTON_COLOR,
+            Interaction::Hovered => HOVERED_BUTTON_COLOR,
+            Interaction::None => BUTTON_COLOR,
+        };
+        if color.0 != wanted {
+            color.0 = wanted;
+        }
+    }
+}
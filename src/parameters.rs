@@ -0,0 +1,353 @@
+//! The continuous values the tuner adjusts on the [`TunerCamera`](crate::TunerCamera), with the
+//! range each is kept within and how fast it changes, shared by every way of adjusting them.
+
+use bevy::core_pipeline::bloom::BloomSettings;
+use bevy::prelude::*;
+use std::fmt;
+
+use crate::bindings::TunerAction;
+
+/// How much a held key changes a bloom parameter per second, before acceleration and modifiers.
+const ADJUST_RATE: f32 = 0.1;
+/// How many degrees a held key changes the FOV per second, before acceleration and modifiers.
+const FOV_ADJUST_RATE: f32 = 20.0;
+/// How much a held key changes the orthographic scale per second, before acceleration and modifiers.
+const ORTHO_SCALE_ADJUST_RATE: f32 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TunerParameter {
+    Intensity,
+    LowFrequencyBoost,
+    LowFrequencyBoostCurvature,
+    HighPassFrequency,
+    Threshold,
+    ThresholdSoftness,
+    /// The FOV in degrees of a perspective projection, or the scale of an orthographic one.
+    Fov,
+}
+
+impl TunerParameter {
+    pub const ALL: [Self; 7] = [
+        Self::Intensity,
+        Self::LowFrequencyBoost,
+        Self::LowFrequencyBoostCurvature,
+        Self::HighPassFrequency,
+        Self::Threshold,
+        Self::ThresholdSoftness,
+        Self::Fov,
+    ];
+
+    pub fn name(self, projection: &Projection) -> &'static str {
+        match self {
+            Self::Intensity => "Intensity",
+            Self::LowFrequencyBoost => "Low-frequency boost",
+            Self::LowFrequencyBoostCurvature => "Low-frequency boost curvature",
+            Self::HighPassFrequency => "High-pass frequency",
+            Self::Threshold => "Threshold",
+            Self::ThresholdSoftness => "Threshold softness",
+            Self::Fov => match projection {
+                Projection::Perspective(_) => "FOV",
+                Projection::Orthographic(_) => "Ortho scale",
+            },
+        }
+    }
+
+    /// The keys increasing and decreasing the parameter.
+    pub fn actions(self) -> (TunerAction, TunerAction) {
+        use TunerAction::*;
+
+        match self {
+            Self::Intensity => (IntensityUp, IntensityDown),
+            Self::LowFrequencyBoost => (LowFrequencyBoostUp, LowFrequencyBoostDown),
+            Self::LowFrequencyBoostCurvature => {
+                (LowFrequencyBoostCurvatureUp, LowFrequencyBoostCurvatureDown)
+            }
+            Self::HighPassFrequency => (HighPassFrequencyUp, HighPassFrequencyDown),
+            Self::Threshold => (ThresholdUp, ThresholdDown),
+            Self::ThresholdSoftness => (ThresholdSoftnessUp, ThresholdSoftnessDown),
+            Self::Fov => (FovUp, FovDown),
+        }
+    }
+
+    /// The smallest and largest values allowed, either of which may be infinite.
+    pub fn range(self, projection: &Projection) -> (f32, f32) {
+        match self {
+            Self::Intensity
+            | Self::LowFrequencyBoostCurvature
+            | Self::HighPassFrequency
+            | Self::ThresholdSoftness => (0.0, 1.0),
+            Self::LowFrequencyBoost => (f32::NEG_INFINITY, f32::INFINITY),
+            Self::Threshold => (0.0, f32::INFINITY),
+            Self::Fov => match projection {
+                // The ends of 0 to 180 degrees squash the view to nothing or turn it inside out.
+                Projection::Perspective(_) => (1.0, 179.0),
+                Projection::Orthographic(_) => (0.1, f32::INFINITY),
+            },
+        }
+    }
+
+    /// Parses a typed value, accepting it only if it is within the [`range`](Self::range) rather
+    /// than clamping it.
+    pub fn parse_value(self, input: &str, projection: &Projection) -> Result<f32, ValueError> {
+        let input = input.trim();
+        let value = input
+            .parse::<f32>()
+            .ok()
+            .filter(|value| value.is_finite())
+            .ok_or_else(|| ValueError::NotANumber(input.to_string()))?;
+
+        let (min, max) = self.range(projection);
+        if !(min..=max).contains(&value) {
+            return Err(ValueError::OutOfRange { value, min, max });
+        }
+
+        Ok(value)
+    }
+
+    /// The part of the [`range`](Self::range) covered by a slider, which is always finite.
+    pub fn slider_range(self, projection: &Projection) -> (f32, f32) {
+        match (self, projection) {
+            (Self::LowFrequencyBoost, _) => (0.0, 1.0),
+            (Self::Threshold, _) => (0.0, 4.0),
+            (Self::Fov, Projection::Orthographic(_)) => (0.1, 10.0),
+            _ => self.range(projection),
+        }
+    }
+
+    /// How much the parameter changes per second while adjusted, before acceleration and
+    /// modifiers.
+    pub fn adjust_rate(self, projection: &Projection) -> f32 {
+        match (self, projection) {
+            (Self::Fov, Projection::Perspective(_)) => FOV_ADJUST_RATE,
+            (Self::Fov, Projection::Orthographic(_)) => ORTHO_SCALE_ADJUST_RATE,
+            _ => ADJUST_RATE,
+        }
+    }
+
+    /// The current value, or `None` for a bloom parameter while bloom is off.
+    pub fn get(
+        self,
+        bloom_settings: Option<&BloomSettings>,
+        projection: &Projection,
+    ) -> Option<f32> {
+        let value = match (self, bloom_settings) {
+            (Self::Fov, _) => match projection {
+                Projection::Perspective(persp) => persp.fov.to_degrees(),
+                Projection::Orthographic(ortho) => ortho.scale,
+            },
+            (_, None) => return None,
+            (Self::Intensity, Some(bloom)) => bloom.intensity,
+            (Self::LowFrequencyBoost, Some(bloom)) => bloom.low_frequency_boost,
+            (Self::LowFrequencyBoostCurvature, Some(bloom)) => bloom.low_frequency_boost_curvature,
+            (Self::HighPassFrequency, Some(bloom)) => bloom.high_pass_frequency,
+            (Self::Threshold, Some(bloom)) => bloom.prefilter_settings.threshold,
+            (Self::ThresholdSoftness, Some(bloom)) => bloom.prefilter_settings.threshold_softness,
+        };
+
+        Some(value)
+    }
+
+    /// Sets the parameter, clamped to its [`range`](Self::range). Returns whether it was set, which
+    /// a bloom parameter is not while bloom is off.
+    pub fn set(
+        self,
+        bloom_settings: Option<&mut BloomSettings>,
+        projection: &mut Projection,
+        value: f32,
+    ) -> bool {
+        let (min, max) = self.range(projection);
+        let value = value.clamp(min, max);
+
+        match (self, bloom_settings) {
+            (Self::Fov, _) => match projection {
+                Projection::Perspective(persp) => persp.fov = value.to_radians(),
+                Projection::Orthographic(ortho) => ortho.scale = value,
+            },
+            (_, None) => return false,
+            (Self::Intensity, Some(bloom)) => bloom.intensity = value,
+            (Self::LowFrequencyBoost, Some(bloom)) => bloom.low_frequency_boost = value,
+            (Self::LowFrequencyBoostCurvature, Some(bloom)) => {
+                bloom.low_frequency_boost_curvature = value;
+            }
+            (Self::HighPassFrequency, Some(bloom)) => bloom.high_pass_frequency = value,
+            (Self::Threshold, Some(bloom)) => bloom.prefilter_settings.threshold = value,
+            (Self::ThresholdSoftness, Some(bloom)) => {
+                bloom.prefilter_settings.threshold_softness = value;
+            }
+        }
+
+        true
+    }
+
+    /// Changes the parameter by `change`, clamped to its [`range`](Self::range). Returns whether it
+    /// was changed, as with [`set`](Self::set).
+    pub fn adjust(
+        self,
+        bloom_settings: Option<&mut BloomSettings>,
+        projection: &mut Projection,
+        change: f32,
+    ) -> bool {
+        let Some(value) = self.get(bloom_settings.as_deref(), projection) else {
+            return false;
+        };
+        self.set(bloom_settings, projection, value + change)
+    }
+}
+
+/// Why a typed value was not accepted.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueError {
+    NotANumber(String),
+    OutOfRange { value: f32, min: f32, max: f32 },
+}
+
+impl fmt::Display for ValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::NotANumber(ref input) => write!(f, "\"{input}\" is not a number"),
+            Self::OutOfRange { value, min, max } if max.is_infinite() => {
+                write!(f, "{value} is out of range, it must be at least {min}")
+            }
+            Self::OutOfRange { value, min, max } => {
+                write!(f, "{value} is out of range, it must be from {min} to {max}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValueError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn perspective() -> Projection {
+        Projection::Perspective(default())
+    }
+
+    fn orthographic() -> Projection {
+        Projection::Orthographic(OrthographicProjection::default())
+    }
+
+    #[test]
+    fn parse_value_accepts_values_in_range() {
+        let projection = perspective();
+
+        assert_eq!(
+            TunerParameter::Intensity.parse_value("0.25", &projection),
+            Ok(0.25)
+        );
+        assert_eq!(
+            TunerParameter::Threshold.parse_value(" 1.5 ", &projection),
+            Ok(1.5)
+        );
+        assert_eq!(
+            TunerParameter::LowFrequencyBoost.parse_value("-3", &projection),
+            Ok(-3.0)
+        );
+        assert_eq!(
+            TunerParameter::Fov.parse_value("1e1", &projection),
+            Ok(10.0)
+        );
+    }
+
+    #[test]
+    fn parse_value_accepts_inclusive_bounds() {
+        let projection = perspective();
+
+        assert_eq!(
+            TunerParameter::Intensity.parse_value("0", &projection),
+            Ok(0.0)
+        );
+        assert_eq!(
+            TunerParameter::Intensity.parse_value("1", &projection),
+            Ok(1.0)
+        );
+        assert_eq!(TunerParameter::Fov.parse_value("1", &projection), Ok(1.0));
+        assert_eq!(
+            TunerParameter::Fov.parse_value("179", &projection),
+            Ok(179.0)
+        );
+        assert_eq!(
+            TunerParameter::Fov.parse_value("0.1", &orthographic()),
+            Ok(0.1)
+        );
+    }
+
+    #[test]
+    fn parse_value_rejects_values_out_of_range() {
+        let projection = perspective();
+
+        assert_eq!(
+            TunerParameter::ThresholdSoftness.parse_value("1.5", &projection),
+            Err(ValueError::OutOfRange {
+                value: 1.5,
+                min: 0.0,
+                max: 1.0
+            })
+        );
+        assert_eq!(
+            TunerParameter::Threshold.parse_value("-0.5", &projection),
+            Err(ValueError::OutOfRange {
+                value: -0.5,
+                min: 0.0,
+                max: f32::INFINITY
+            })
+        );
+        for (input, value) in [("0", 0.0), ("180", 180.0)] {
+            assert_eq!(
+                TunerParameter::Fov.parse_value(input, &projection),
+                Err(ValueError::OutOfRange {
+                    value,
+                    min: 1.0,
+                    max: 179.0
+                })
+            );
+        }
+        assert_eq!(
+            TunerParameter::Fov.parse_value("0.05", &orthographic()),
+            Err(ValueError::OutOfRange {
+                value: 0.05,
+                min: 0.1,
+                max: f32::INFINITY
+            })
+        );
+    }
+
+    #[test]
+    fn parse_value_rejects_non_numbers() {
+        let projection = perspective();
+
+        for input in ["", "abc", "1.2.3", "NaN", "inf", "-inf", "1e39"] {
+            assert_eq!(
+                TunerParameter::LowFrequencyBoost.parse_value(input, &projection),
+                Err(ValueError::NotANumber(input.to_string())),
+                "{input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn value_error_messages() {
+        let projection = perspective();
+        let message = |parameter: TunerParameter, input| {
+            parameter
+                .parse_value(input, &projection)
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert_eq!(
+            message(TunerParameter::Intensity, "2"),
+            "2 is out of range, it must be from 0 to 1"
+        );
+        assert_eq!(
+            message(TunerParameter::Threshold, "-1"),
+            "-1 is out of range, it must be at least 0"
+        );
+        assert_eq!(
+            message(TunerParameter::Intensity, " x "),
+            "\"x\" is not a number"
+        );
+    }
+}
@@ -0,0 +1,321 @@
+//! A simple deterministic simulation of the grid spheres falling under gravity, bouncing off the
+//! ground and colliding with each other, as an alternative to [`BounceMotion`](crate::motion::BounceMotion).
+
+use bevy::diagnostic::Diagnostics;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, Instant};
+
+use crate::sphere_grid::{SphereGridConfig, COLLIDE_BODIES_TIME};
+
+/// How the simulated spheres behave. Set through
+/// [`SphereGridPlugin::with_physics`](crate::SphereGridPlugin::with_physics).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PhysicsSettings {
+    pub gravity: Vec3,
+    /// Fraction of the speed kept when bouncing off the ground or another sphere.
+    pub restitution: f32,
+    /// Fraction of the sideways speed lost on each bounce off the ground.
+    pub friction: f32,
+    /// Height of the ground plane the spheres rest on.
+    pub ground_height: f32,
+    /// Highest height above the ground a sphere is dropped from.
+    pub drop_height: f32,
+    /// Greatest sideways speed a sphere starts with, so neighbours run into each other.
+    pub max_initial_speed: f32,
+}
+
+impl Default for PhysicsSettings {
+    fn default() -> Self {
+        Self {
+            gravity: Vec3::new(0.0, -9.81, 0.0),
+            restitution: 0.8,
+            friction: 0.05,
+            ground_height: -1.0,
+            drop_height: 5.0,
+            max_initial_speed: 1.0,
+        }
+    }
+}
+
+/// A grid sphere moved by the simulation rather than a [`BounceMotion`](crate::motion::BounceMotion).
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct PhysicsBody {
+    pub velocity: Vec3,
+}
+
+// ------------------------------------------------------------------------------------------------
+
+pub(crate) fn apply_gravity(
+    time: Res<Time>,
+    config: Res<SphereGridConfig>,
+    mut bodies: Query<&mut PhysicsBody>,
+) {
+    let Some(physics) = config.physics else {
+        return;
+    };
+
+    for mut body in bodies.iter_mut() {
+        body.velocity += physics.gravity * time.delta_seconds();
+    }
+}
+
+pub(crate) fn move_bodies(time: Res<Time>, mut bodies: Query<(&mut Transform, &PhysicsBody)>) {
+    for (mut transform, body) in bodies.iter_mut() {
+        transform.translation += body.velocity * time.delta_seconds();
+    }
+}
+
+/// Pushes two overlapping spheres apart and exchanges momentum between them. Every sphere has the
+/// same radius and mass.
+fn collide_pair(a: &mut (Vec3, Vec3), b: &mut (Vec3, Vec3), min_distance: f32, restitution: f32) {
+    let offset = b.0 - a.0;
+    let distance = offset.length();
+    if distance >= min_distance {
+        return;
+    }
+
+    // Spheres at exactly the same place have no direction to separate in, so pick one.
+    let normal = offset.try_normalize().unwrap_or(Vec3::Y);
+    let correction = normal * (min_distance - distance) / 2.0;
+    a.0 -= correction;
+    b.0 += correction;
+
+    let approach_speed = (b.1 - a.1).dot(normal);
+    if approach_speed < 0.0 {
+        let impulse = normal * -(1.0 + restitution) * approach_speed / 2.0;
+        a.1 -= impulse;
+        b.1 += impulse;
+    }
+}
+
+/// Finds the pairs of spheres close enough to collide. The spheres are sorted into a grid of cells
+/// as wide as a sphere, so each one is only tested against those in its own and neighbouring cells.
+#[derive(Default)]
+pub(crate) struct BroadPhase {
+    cells: HashMap<IVec3, Vec<usize>>,
+    pairs: Vec<(usize, usize)>,
+}
+
+impl BroadPhase {
+    fn cell_of(position: Vec3, cell_size: f32) -> IVec3 {
+        (position / cell_size).floor().as_ivec3()
+    }
+
+    /// Every pair of spheres in the same or neighbouring cells, by index into `positions`, each
+    /// once with the lower index first.
+    fn find_pairs(
+        &mut self,
+        positions: impl IntoIterator<Item = Vec3>,
+        cell_size: f32,
+    ) -> &[(usize, usize)] {
+        let body_cells: Vec<_> = positions
+            .into_iter()
+            .map(|position| Self::cell_of(position, cell_size))
+            .collect();
+
+        self.cells.clear();
+        for (index, &cell) in body_cells.iter().enumerate() {
+            self.cells.entry(cell).or_default().push(index);
+        }
+
+        self.pairs.clear();
+        for (a, &cell) in body_cells.iter().enumerate() {
+            for x in -1..=1 {
+                for y in -1..=1 {
+                    for z in -1..=1 {
+                        let Some(neighbours) = self.cells.get(&(cell + IVec3::new(x, y, z))) else {
+                            continue;
+                        };
+
+                        // Each pair is only listed once, by the body which comes first.
+                        self.pairs
+                            .extend(neighbours.iter().filter(|&&b| b > a).map(|&b| (a, b)));
+                    }
+                }
+            }
+        }
+
+        &self.pairs
+    }
+}
+
+/// Collides every pair of overlapping spheres.
+pub(crate) fn collide_bodies(
+    config: Res<SphereGridConfig>,
+    mut bodies: Query<(Entity, &mut Transform, &mut PhysicsBody)>,
+    mut broad_phase: Local<BroadPhase>,
+    mut diagnostics: Diagnostics,
+) {
+    let Some(physics) = config.physics else {
+        return;
+    };
+    let start = Instant::now();
+    let min_distance = config.radius * 2.0;
+
+    // Positions and velocities, which the pairs refer to by index.
+    let mut states: Vec<_> = bodies
+        .iter()
+        .map(|(entity, transform, body)| (entity, (transform.translation, body.velocity)))
+        .collect();
+
+    // Paired by where they were before any were pushed apart, as they only move a little.
+    let pairs = broad_phase.find_pairs(
+        states.iter().map(|(_, (position, _))| *position),
+        min_distance,
+    );
+    for &(a, b) in pairs {
+        let (first, second) = states.split_at_mut(b);
+        collide_pair(
+            &mut first[a].1,
+            &mut second[0].1,
+            min_distance,
+            physics.restitution,
+        );
+    }
+
+    // Only bodies which collided are written back, so the rest are not marked as changed.
+    for (entity, (position, velocity)) in states {
+        let Ok((_, mut transform, mut body)) = bodies.get_mut(entity) else {
+            continue;
+        };
+        if transform.translation != position {
+            transform.translation = position;
+        }
+        if body.velocity != velocity {
+            body.velocity = velocity;
+        }
+    }
+
+    diagnostics.add_measurement(&COLLIDE_BODIES_TIME, || {
+        start.elapsed().as_secs_f64() * 1000.0
+    });
+}
+
+pub(crate) fn collide_with_ground(
+    config: Res<SphereGridConfig>,
+    mut bodies: Query<(&mut Transform, &mut PhysicsBody)>,
+) {
+    let Some(physics) = config.physics else {
+        return;
+    };
+    let rest_height = physics.ground_height + config.radius;
+
+    for (mut transform, mut body) in bodies.iter_mut() {
+        if transform.translation.y > rest_height {
+            continue;
+        }
+
+        transform.translation.y = rest_height;
+        if body.velocity.y < 0.0 {
+            body.velocity.y *= -physics.restitution;
+            body.velocity.x *= 1.0 - physics.friction;
+            body.velocity.z *= 1.0 - physics.friction;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    const RADIUS: f32 = 0.5;
+    const MIN_DISTANCE: f32 = RADIUS * 2.0;
+
+    #[test]
+    fn overlapping_spheres_in_neighbouring_cells_are_pushed_apart() {
+        let mut positions = [Vec3::new(0.95, 0.2, 0.0), Vec3::new(1.05, 0.3, 0.0)];
+        assert_ne!(
+            BroadPhase::cell_of(positions[0], MIN_DISTANCE),
+            BroadPhase::cell_of(positions[1], MIN_DISTANCE)
+        );
+
+        let mut broad_phase = BroadPhase::default();
+        assert_eq!(broad_phase.find_pairs(positions, MIN_DISTANCE), [(0, 1)]);
+
+        let mut a = (positions[0], Vec3::ZERO);
+        let mut b = (positions[1], Vec3::ZERO);
+        collide_pair(&mut a, &mut b, MIN_DISTANCE, 1.0);
+        positions = [a.0, b.0];
+        assert!(positions[0].distance(positions[1]) >= MIN_DISTANCE - 1e-5);
+        assert!(positions[0].x < 0.95 && positions[1].x > 1.05);
+    }
+
+    #[test]
+    fn each_pair_is_found_once() {
+        let positions = [
+            Vec3::new(0.1, 0.1, 0.1),
+            Vec3::new(0.9, 0.1, 0.1),
+            Vec3::new(1.1, 0.1, 0.1),
+            Vec3::new(0.5, 0.9, 0.5),
+        ];
+
+        let mut broad_phase = BroadPhase::default();
+        let mut pairs = broad_phase.find_pairs(positions, MIN_DISTANCE).to_vec();
+        pairs.sort_unstable();
+
+        assert_eq!(pairs, [(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]);
+    }
+
+    #[test]
+    fn finds_every_overlapping_pair() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let positions: Vec<_> = (0..500)
+            .map(|_| {
+                Vec3::new(
+                    rng.gen_range(-5.0..5.0),
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-5.0..5.0),
+                )
+            })
+            .collect();
+
+        let mut broad_phase = BroadPhase::default();
+        let pairs = broad_phase.find_pairs(positions.iter().copied(), MIN_DISTANCE);
+
+        let mut unique = pairs.to_vec();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(unique.len(), pairs.len());
+        assert!(pairs.iter().all(|&(a, b)| a < b));
+
+        for a in 0..positions.len() {
+            for b in a + 1..positions.len() {
+                if positions[a].distance(positions[b]) < MIN_DISTANCE {
+                    assert!(pairs.contains(&(a, b)), "missed {a} and {b}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn spheres_at_the_same_position_separate_along_y() {
+        let mut a = (Vec3::ONE, Vec3::ZERO);
+        let mut b = (Vec3::ONE, Vec3::ZERO);
+        collide_pair(&mut a, &mut b, MIN_DISTANCE, 0.8);
+
+        assert_eq!(a.0, Vec3::ONE - Vec3::Y * RADIUS);
+        assert_eq!(b.0, Vec3::ONE + Vec3::Y * RADIUS);
+    }
+
+    #[test]
+    fn head_on_collision_exchanges_velocities() {
+        let mut a = (Vec3::ZERO, Vec3::X);
+        let mut b = (Vec3::X * 0.9, -Vec3::X);
+        collide_pair(&mut a, &mut b, MIN_DISTANCE, 1.0);
+
+        assert!(a.1.abs_diff_eq(-Vec3::X, 1e-5));
+        assert!(b.1.abs_diff_eq(Vec3::X, 1e-5));
+    }
+
+    #[test]
+    fn separate_spheres_are_left_alone() {
+        let mut a = (Vec3::ZERO, Vec3::X);
+        let mut b = (Vec3::X * 1.5, -Vec3::X);
+        collide_pair(&mut a, &mut b, MIN_DISTANCE, 1.0);
+
+        assert_eq!(a, (Vec3::ZERO, Vec3::X));
+        assert_eq!(b, (Vec3::X * 1.5, -Vec3::X));
+    }
+}
@@ -0,0 +1,346 @@
+//! Named `BloomSettings` presets which can be saved to and loaded from RON files.
+
+use bevy::core_pipeline::{
+    bloom::{BloomCompositeMode, BloomPrefilterSettings, BloomSettings},
+    tonemapping::Tonemapping,
+};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::bindings::{TunerAction, TunerBindings};
+use crate::tuner::{TunerCamera, TunerConfig};
+
+pub const DEFAULT_PRESET_DIR: &str = "assets/presets";
+const PRESET_EXTENSION: &str = "ron";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompositeMode {
+    EnergyConserving,
+    Additive,
+}
+
+impl From<BloomCompositeMode> for CompositeMode {
+    fn from(mode: BloomCompositeMode) -> Self {
+        match mode {
+            BloomCompositeMode::EnergyConserving => Self::EnergyConserving,
+            BloomCompositeMode::Additive => Self::Additive,
+        }
+    }
+}
+
+impl From<CompositeMode> for BloomCompositeMode {
+    fn from(mode: CompositeMode) -> Self {
+        match mode {
+            CompositeMode::EnergyConserving => Self::EnergyConserving,
+            CompositeMode::Additive => Self::Additive,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TonemappingMode {
+    None,
+    Reinhard,
+    ReinhardLuminance,
+    AcesFitted,
+    AgX,
+    SomewhatBoringDisplayTransform,
+    #[default]
+    TonyMcMapface,
+    BlenderFilmic,
+}
+
+impl From<Tonemapping> for TonemappingMode {
+    fn from(tonemapping: Tonemapping) -> Self {
+        match tonemapping {
+            Tonemapping::None => Self::None,
+            Tonemapping::Reinhard => Self::Reinhard,
+            Tonemapping::ReinhardLuminance => Self::ReinhardLuminance,
+            Tonemapping::AcesFitted => Self::AcesFitted,
+            Tonemapping::AgX => Self::AgX,
+            Tonemapping::SomewhatBoringDisplayTransform => Self::SomewhatBoringDisplayTransform,
+            Tonemapping::TonyMcMapface => Self::TonyMcMapface,
+            Tonemapping::BlenderFilmic => Self::BlenderFilmic,
+        }
+    }
+}
+
+impl From<TonemappingMode> for Tonemapping {
+    fn from(mode: TonemappingMode) -> Self {
+        match mode {
+            TonemappingMode::None => Self::None,
+            TonemappingMode::Reinhard => Self::Reinhard,
+            TonemappingMode::ReinhardLuminance => Self::ReinhardLuminance,
+            TonemappingMode::AcesFitted => Self::AcesFitted,
+            TonemappingMode::AgX => Self::AgX,
+            TonemappingMode::SomewhatBoringDisplayTransform => Self::SomewhatBoringDisplayTransform,
+            TonemappingMode::TonyMcMapface => Self::TonyMcMapface,
+            TonemappingMode::BlenderFilmic => Self::BlenderFilmic,
+        }
+    }
+}
+
+/// A serializable snapshot of every tunable `BloomSettings` field and the camera's tonemapper.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BloomPreset {
+    pub name: String,
+    pub intensity: f32,
+    pub low_frequency_boost: f32,
+    pub low_frequency_boost_curvature: f32,
+    pub high_pass_frequency: f32,
+    pub composite_mode: CompositeMode,
+    pub threshold: f32,
+    pub threshold_softness: f32,
+    /// Missing from presets saved before tonemapping was tunable, which used TonyMcMapface.
+    #[serde(default)]
+    pub tonemapping: TonemappingMode,
+}
+
+impl BloomPreset {
+    pub fn from_settings(
+        name: impl Into<String>,
+        settings: &BloomSettings,
+        tonemapping: Tonemapping,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            intensity: settings.intensity,
+            low_frequency_boost: settings.low_frequency_boost,
+            low_frequency_boost_curvature: settings.low_frequency_boost_curvature,
+            high_pass_frequency: settings.high_pass_frequency,
+            composite_mode: settings.composite_mode.into(),
+            threshold: settings.prefilter_settings.threshold,
+            threshold_softness: settings.prefilter_settings.threshold_softness,
+            tonemapping: tonemapping.into(),
+        }
+    }
+
+    pub fn to_settings(&self) -> BloomSettings {
+        BloomSettings {
+            intensity: self.intensity,
+            low_frequency_boost: self.low_frequency_boost,
+            low_frequency_boost_curvature: self.low_frequency_boost_curvature,
+            high_pass_frequency: self.high_pass_frequency,
+            prefilter_settings: BloomPrefilterSettings {
+                threshold: self.threshold,
+                threshold_softness: self.threshold_softness,
+            },
+            composite_mode: self.composite_mode.into(),
+        }
+    }
+
+    pub fn tonemapping(&self) -> Tonemapping {
+        self.tonemapping.into()
+    }
+
+    pub fn path(&self, dir: &Path) -> PathBuf {
+        preset_path(dir, &self.name)
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<(), PresetError> {
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+
+        fs::create_dir_all(dir)?;
+        fs::write(self.path(dir), contents)?;
+
+        Ok(())
+    }
+
+    pub fn load(dir: &Path, name: &str) -> Result<Self, PresetError> {
+        let contents = fs::read_to_string(preset_path(dir, name))?;
+
+        Ok(ron::from_str(&contents)?)
+    }
+}
+
+pub fn preset_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{name}.{PRESET_EXTENSION}"))
+}
+
+/// Lists the names of every preset file in `dir`, sorted alphabetically.
+pub fn list_presets(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut names = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == PRESET_EXTENSION))
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+        .collect::<Vec<_>>();
+    names.sort();
+
+    names
+}
+
+#[derive(Debug)]
+pub enum PresetError {
+    Io(io::Error),
+    Serialize(ron::Error),
+    Deserialize(ron::error::SpannedError),
+}
+
+impl fmt::Display for PresetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Serialize(err) => write!(f, "could not serialize preset: {err}"),
+            Self::Deserialize(err) => write!(f, "could not parse preset: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for PresetError {}
+
+impl From<io::Error> for PresetError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ron::Error> for PresetError {
+    fn from(err: ron::Error) -> Self {
+        Self::Serialize(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for PresetError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        Self::Deserialize(err)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+/// The presets found on disk and the one currently targeted by the save/load hotkeys.
+#[derive(Resource)]
+pub struct PresetSelection {
+    pub names: Vec<String>,
+    pub index: usize,
+}
+
+impl PresetSelection {
+    pub fn new(dir: &Path) -> Self {
+        let mut names = list_presets(dir);
+        if names.is_empty() {
+            names.push("default".to_string());
+        }
+
+        Self { names, index: 0 }
+    }
+
+    pub fn current(&self) -> &str {
+        &self.names[self.index]
+    }
+
+    /// Selects the named preset, adding it to the list if it is not on disk yet.
+    pub fn select(&mut self, name: &str) {
+        self.index = match self.names.iter().position(|n| n == name) {
+            Some(index) => index,
+            None => {
+                self.names.push(name.to_string());
+                self.names.len() - 1
+            }
+        };
+    }
+
+    fn select_next(&mut self) {
+        self.index = (self.index + 1) % self.names.len();
+    }
+
+    fn add_new(&mut self) -> &str {
+        let name = (1..)
+            .map(|i| format!("preset_{i}"))
+            .find(|name| !self.names.contains(name))
+            .unwrap();
+
+        self.names.push(name);
+        self.index = self.names.len() - 1;
+
+        self.current()
+    }
+}
+
+/// Saves the [`TunerCamera`] bloom settings over the selected preset or as a new preset, selects
+/// the next preset, and loads the selected preset onto the [`TunerCamera`].
+pub(crate) fn handle_preset_keys(
+    mut commands: Commands,
+    keycode: Res<ButtonInput<KeyCode>>,
+    bindings: Res<TunerBindings>,
+    config: Res<TunerConfig>,
+    mut selection: ResMut<PresetSelection>,
+    camera: Query<(Entity, Option<&BloomSettings>, &Tonemapping), With<TunerCamera>>,
+) {
+    let Ok((entity, bloom_settings, tonemapping)) = camera.get_single() else {
+        return;
+    };
+
+    let save_new = bindings.just_pressed(&keycode, TunerAction::SaveNewPreset);
+
+    if bindings.just_pressed(&keycode, TunerAction::NextPreset) {
+        selection.select_next();
+    }
+
+    if save_new || bindings.just_pressed(&keycode, TunerAction::SavePreset) {
+        let Some(bloom_settings) = bloom_settings else {
+            warn!("Bloom is off, there are no settings to save");
+            return;
+        };
+
+        let name = if save_new {
+            selection.add_new()
+        } else {
+            selection.current()
+        };
+
+        let preset = BloomPreset::from_settings(name, bloom_settings, *tonemapping);
+        let path = preset.path(&config.preset_dir);
+        match preset.save(&config.preset_dir) {
+            Ok(()) => info!("Saved bloom preset to {}", path.display()),
+            Err(err) => error!("Failed to save {}: {err}", path.display()),
+        }
+    }
+
+    if bindings.just_pressed(&keycode, TunerAction::LoadPreset) {
+        match BloomPreset::load(&config.preset_dir, selection.current()) {
+            Ok(preset) => {
+                commands
+                    .entity(entity)
+                    .insert((preset.to_settings(), preset.tonemapping()));
+            }
+            Err(err) => error!(
+                "Failed to load {}: {err}",
+                preset_path(&config.preset_dir, selection.current()).display()
+            ),
+        }
+    }
+}
+
+/// Loads [`TunerConfig::startup_preset`] onto the [`TunerCamera`], which is spawned during
+/// `Startup`.
+pub(crate) fn apply_startup_preset(
+    mut commands: Commands,
+    config: Res<TunerConfig>,
+    camera: Query<Entity, With<TunerCamera>>,
+) {
+    let (Some(name), Ok(entity)) = (&config.startup_preset, camera.get_single()) else {
+        return;
+    };
+
+    match BloomPreset::load(&config.preset_dir, name) {
+        Ok(preset) => {
+            commands
+                .entity(entity)
+                .insert((preset.to_settings(), preset.tonemapping()));
+        }
+        Err(err) => error!(
+            "Failed to load {}: {err}",
+            preset_path(&config.preset_dir, name).display()
+        ),
+    }
+}
@@ -0,0 +1,280 @@
+//! An in-app screen listing every flycam and tuner action, where any of them can be bound to a new
+//! key. Rebinds which would conflict are refused, and accepted ones are saved straight away.
+
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::ui::{FocusPolicy, UiSystem};
+use bevy_flycam::prelude::KeyBindings;
+
+use crate::bindings::{learn_keyboard_layout, KeyboardLayout, TunerAction, TunerBindings};
+use crate::conflicts::{try_rebind, BoundAction, FlycamAction};
+use crate::flycam::UserSettingsFile;
+use crate::tuner::TunerConfig;
+use crate::user_settings::{FlycamKeys, SettingsError, UserSettings};
+
+/// How many actions are listed at once; the list scrolls to keep the selected one in view.
+const VISIBLE_ROWS: usize = 20;
+
+/// Opens the bindings screen with the [`TunerAction::OpenBindings`] key. While it is open it takes
+/// all keyboard and mouse button input. Rebound tuner keys are saved to the
+/// [`TunerConfig::user_bindings_path`], and flycam bindings with the [`UserSettings`] when
+/// [`FlycamControlPlugin`](crate::FlycamControlPlugin) is added. Requires
+/// [`BloomTunerPlugin`](crate::BloomTunerPlugin).
+pub struct BindingsScreenPlugin;
+
+impl Plugin for BindingsScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BindingsScreen>()
+            .add_systems(Startup, setup_bindings_screen)
+            .add_systems(
+                PreUpdate,
+                handle_bindings_screen
+                    .after(InputSystem)
+                    .after(learn_keyboard_layout)
+                    // Clears the mouse buttons before the UI reacts to them.
+                    .before(UiSystem::Focus),
+            )
+            .add_systems(Update, update_bindings_screen);
+    }
+}
+
+/// The state of the bindings screen.
+#[derive(Resource, Default, Debug)]
+pub struct BindingsScreen {
+    pub open: bool,
+    /// The selected row.
+    pub index: usize,
+    /// Whether the next key pressed is bound to the selected action.
+    pub listening: bool,
+    /// The outcome of the last rebind.
+    pub status: String,
+}
+
+/// Marks the node covering the window while the bindings screen is open.
+#[derive(Component)]
+pub struct BindingsScreenRoot;
+
+/// Marks the text entity listing the bindings.
+#[derive(Component)]
+pub struct BindingsScreenText;
+
+/// Every action listed on the screen, flycam movement first.
+fn rows(has_flycam: bool) -> Vec<BoundAction> {
+    let flycam = FlycamAction::ALL
+        .into_iter()
+        .filter(|_| has_flycam)
+        .map(BoundAction::Flycam);
+    let tuner = TunerAction::ALL.into_iter().map(BoundAction::Tuner);
+
+    flycam.chain(tuner).collect()
+}
+
+// ------------------------------------------------------------------------------------------------
+
+fn setup_bindings_screen(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    padding: UiRect::all(Val::Px(24.0)),
+                    display: Display::None,
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.85).into(),
+                z_index: ZIndex::Global(10),
+                // Clicks on the screen should not reach the tuner panel under it.
+                focus_policy: FocusPolicy::Block,
+                ..default()
+            },
+            Interaction::default(),
+            BindingsScreenRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                BindingsScreenText,
+            ));
+        });
+}
+
+/// Opens and closes the screen, moves the selection and rebinds. Runs straight after input is
+/// collected, and clears it while the screen is open so nothing else reacts to it.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn handle_bindings_screen(
+    mut keycode: ResMut<ButtonInput<KeyCode>>,
+    mut mouse: ResMut<ButtonInput<MouseButton>>,
+    mut screen: ResMut<BindingsScreen>,
+    mut tuner: ResMut<TunerBindings>,
+    mut flycam: Option<ResMut<KeyBindings>>,
+    mut user_settings: Option<ResMut<UserSettings>>,
+    mut settings_file: Option<ResMut<UserSettingsFile>>,
+    config: Res<TunerConfig>,
+    layout: Res<KeyboardLayout>,
+) {
+    if !screen.open {
+        if tuner.just_pressed(&keycode, TunerAction::OpenBindings) {
+            screen.open = true;
+            screen.listening = false;
+            screen.status.clear();
+            keycode.reset_all();
+            mouse.reset_all();
+        }
+        return;
+    }
+
+    let rows = rows(flycam.is_some());
+    let pressed = keycode.get_just_pressed().next().copied();
+
+    if screen.listening {
+        if mouse.just_pressed(MouseButton::Right) {
+            screen.listening = false;
+        } else if let (Some(key), Some(&action)) = (pressed, rows.get(screen.index)) {
+            screen.listening = false;
+            screen.status = match try_rebind(&mut tuner, flycam.as_deref_mut(), action, key) {
+                Ok(()) => match save_bindings(
+                    action,
+                    &tuner,
+                    flycam.as_deref(),
+                    user_settings.as_deref_mut(),
+                    settings_file.as_deref_mut(),
+                    &config,
+                ) {
+                    Ok(saved_to) => {
+                        format!(
+                            "Bound {action} to {}, saved to {saved_to}",
+                            layout.key_label(key)
+                        )
+                    }
+                    Err(err) => {
+                        error!("Could not save key bindings: {err}");
+                        format!(
+                            "Bound {action} to {}, could not save: {err}",
+                            layout.key_label(key)
+                        )
+                    }
+                },
+                Err(conflict) => format!("Refused, {}", conflict.label(&layout)),
+            };
+        }
+    } else if let Some(key) = pressed {
+        match key {
+            KeyCode::ArrowUp => screen.index = (screen.index + rows.len() - 1) % rows.len(),
+            KeyCode::ArrowDown => screen.index = (screen.index + 1) % rows.len(),
+            KeyCode::Enter => {
+                screen.listening = true;
+                screen.status.clear();
+            }
+            KeyCode::Escape => screen.open = false,
+            _ if key == tuner.key(TunerAction::OpenBindings) => screen.open = false,
+            _ => {}
+        }
+    }
+
+    keycode.reset_all();
+    mouse.reset_all();
+}
+
+/// Saves the bindings `action` belongs to, returning where they were saved.
+fn save_bindings(
+    action: BoundAction,
+    tuner: &TunerBindings,
+    flycam: Option<&KeyBindings>,
+    user_settings: Option<&mut UserSettings>,
+    settings_file: Option<&mut UserSettingsFile>,
+    config: &TunerConfig,
+) -> Result<String, SettingsError> {
+    match (action, flycam, user_settings, settings_file) {
+        (BoundAction::Tuner(_), ..) => {
+            // Only the keys which differ from the defaults are saved, so the defaults file is
+            // left as it is and later changes to it still apply.
+            let defaults = TunerBindings::load_or_default(&config.bindings_path);
+            tuner
+                .overrides(&defaults)
+                .save(&config.user_bindings_path)?;
+            Ok(config.user_bindings_path.display().to_string())
+        }
+        (_, Some(flycam), Some(user_settings), Some(file)) => {
+            user_settings.flycam_keys = FlycamKeys::from(flycam);
+            file.save_changes(user_settings)?;
+            Ok(file.path.display().to_string())
+        }
+        _ => Ok("nowhere, FlycamControlPlugin is needed to keep it".to_string()),
+    }
+}
+
+fn update_bindings_screen(
+    mut root: Query<&mut Style, With<BindingsScreenRoot>>,
+    mut text: Query<&mut Text, With<BindingsScreenText>>,
+    mut screen: ResMut<BindingsScreen>,
+    tuner: Res<TunerBindings>,
+    flycam: Option<Res<KeyBindings>>,
+    layout: Res<KeyboardLayout>,
+) {
+    let (Ok(mut style), Ok(mut text)) = (root.get_single_mut(), text.get_single_mut()) else {
+        return;
+    };
+
+    let display = if screen.open {
+        Display::Flex
+    } else {
+        Display::None
+    };
+    if style.display != display {
+        style.display = display;
+    }
+    if !screen.open {
+        return;
+    }
+
+    let rows = rows(flycam.is_some());
+    screen.index = screen.index.min(rows.len() - 1);
+    let first = screen
+        .index
+        .saturating_sub(VISIBLE_ROWS / 2)
+        .min(rows.len().saturating_sub(VISIBLE_ROWS));
+
+    let text = &mut text.sections[0].value;
+    *text = format!(
+        "Key bindings ({}/{}: select, {}: rebind, {}/{}: close)\n\n",
+        layout.key_label(KeyCode::ArrowUp),
+        layout.key_label(KeyCode::ArrowDown),
+        layout.key_label(KeyCode::Enter),
+        layout.key_label(KeyCode::Escape),
+        tuner.label(TunerAction::OpenBindings, &layout),
+    );
+
+    for (index, action) in rows.iter().enumerate().skip(first).take(VISIBLE_ROWS) {
+        let key = match action {
+            BoundAction::Tuner(action) if action.is_shortcut() => {
+                tuner.shortcut_label(*action, &layout)
+            }
+            BoundAction::Tuner(action) => tuner.label(*action, &layout),
+            BoundAction::Flycam(action) => flycam
+                .as_deref()
+                .map_or_else(String::new, |flycam| layout.key_label(action.key(flycam))),
+            BoundAction::Modifier => String::new(),
+        };
+        let selected = index == screen.index;
+        text.push_str(&format!(
+            "{} {action}: {key}{}\n",
+            if selected { ">" } else { " " },
+            if selected && screen.listening {
+                " (press a new key, right click to cancel)"
+            } else {
+                ""
+            }
+        ));
+    }
+
+    text.push_str(&format!("\n{}\n", screen.status));
+}
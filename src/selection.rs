@@ -0,0 +1,231 @@
+//! Selecting grid spheres by clicking on them, using `Camera::viewport_to_world` to cast a ray from
+//! the cursor into the scene.
+
+use bevy::prelude::*;
+use bevy::window::{CursorGrabMode, PrimaryWindow};
+
+use crate::bindings::{KeyboardLayout, TunerAction, TunerBindings};
+use crate::comparison::{ComparisonCamera, ComparisonPrimary};
+use crate::material_editor::{
+    edit_selected_material, MaterialField, MaterialFieldSelection, OwnMaterial,
+};
+use crate::sphere_grid::{GridSphere, SphereGridConfig};
+use crate::tuner::TunerCamera;
+
+/// Lets the user click a grid sphere through the [`TunerCamera`] to select it, highlighting it,
+/// listing its details in a HUD and letting its material be edited. Requires
+/// [`SphereGridPlugin`](crate::SphereGridPlugin) and [`BloomTunerPlugin`](crate::BloomTunerPlugin).
+pub struct SelectionPlugin;
+
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MaterialFieldSelection>()
+            .add_systems(Startup, setup_selection_hud)
+            .add_systems(
+                Update,
+                (
+                    pick_sphere,
+                    edit_selected_material,
+                    highlight_selected,
+                    update_selection_hud,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Marks the currently selected grid sphere.
+#[derive(Component)]
+pub struct Selected;
+
+/// Marks the text entity listing the selected sphere's details.
+#[derive(Component)]
+pub struct SelectionHud;
+
+// ------------------------------------------------------------------------------------------------
+
+fn setup_selection_hud(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 20.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            left: Val::Px(12.0),
+            ..default()
+        }),
+        SelectionHud,
+    ));
+}
+
+/// The distance along the ray to its first intersection with the sphere, if it hits it.
+fn ray_sphere_intersection(ray: Ray3d, center: Vec3, radius: f32) -> Option<f32> {
+    let offset = ray.origin - center;
+    let b = offset.dot(*ray.direction);
+    let c = offset.length_squared() - radius * radius;
+
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let sqrt = discriminant.sqrt();
+    [-b - sqrt, -b + sqrt].into_iter().find(|&t| t >= 0.0)
+}
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+fn pick_sphere(
+    mut commands: Commands,
+    mouse: Res<ButtonInput<MouseButton>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    // The tuner can be on either side of a comparison, so both sides are picked through.
+    cameras: Query<
+        (&Camera, &GlobalTransform),
+        Or<(
+            With<TunerCamera>,
+            With<ComparisonPrimary>,
+            With<ComparisonCamera>,
+        )>,
+    >,
+    spheres: Query<(Entity, &GlobalTransform), With<GridSphere>>,
+    selected: Query<Entity, With<Selected>>,
+    ui: Query<&Interaction>,
+    config: Res<SphereGridConfig>,
+) {
+    // Clicks on UI such as the tuner panel are left to it.
+    if !mouse.just_pressed(MouseButton::Left)
+        || ui
+            .iter()
+            .any(|interaction| *interaction != Interaction::None)
+    {
+        return;
+    }
+
+    let Ok(window) = window.get_single() else {
+        return;
+    };
+
+    // While the flycam has grabbed the cursor it stays hidden in the middle of the window, so pick
+    // whatever is under the crosshair instead.
+    let cursor = match window.cursor.grab_mode {
+        CursorGrabMode::None => window.cursor_position(),
+        _ => Some(Vec2::new(window.width(), window.height()) / 2.0),
+    };
+
+    // During an A/B comparison each camera renders part of the window, so cast the ray through
+    // whichever one is under the cursor.
+    let Some(ray) = cursor.and_then(|cursor| {
+        cameras.iter().find_map(|(camera, camera_transform)| {
+            let viewport = camera.logical_viewport_rect()?;
+            if !viewport.contains(cursor) {
+                return None;
+            }
+
+            camera.viewport_to_world(camera_transform, cursor - viewport.min)
+        })
+    }) else {
+        return;
+    };
+
+    let hit = spheres
+        .iter()
+        .filter_map(|(entity, transform)| {
+            let (scale, _, center) = transform.to_scale_rotation_translation();
+            let distance =
+                ray_sphere_intersection(ray, center, config.radius * scale.max_element())?;
+
+            Some((entity, distance))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(entity, _)| entity);
+
+    for entity in &selected {
+        commands.entity(entity).remove::<Selected>();
+    }
+
+    if let Some(entity) = hit {
+        commands.entity(entity).insert(Selected);
+    }
+}
+
+fn highlight_selected(
+    mut gizmos: Gizmos,
+    selected: Query<&GlobalTransform, With<Selected>>,
+    config: Res<SphereGridConfig>,
+) {
+    for transform in &selected {
+        gizmos.sphere(
+            transform.translation(),
+            Quat::IDENTITY,
+            config.radius * 1.2,
+            Color::YELLOW,
+        );
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_selection_hud(
+    mut text: Query<&mut Text, With<SelectionHud>>,
+    selected: Query<
+        (
+            &GridSphere,
+            &Transform,
+            &Handle<StandardMaterial>,
+            Has<OwnMaterial>,
+        ),
+        With<Selected>,
+    >,
+    materials: Res<Assets<StandardMaterial>>,
+    bindings: Res<TunerBindings>,
+    layout: Res<KeyboardLayout>,
+    field_selection: Res<MaterialFieldSelection>,
+) {
+    use TunerAction::*;
+
+    let Ok(mut text) = text.get_single_mut() else {
+        return;
+    };
+    let text = &mut text.sections[0].value;
+
+    let Ok((sphere, transform, handle, has_own_material)) = selected.get_single() else {
+        *text = "Click a sphere to select it".to_string();
+        return;
+    };
+
+    *text = "Selected sphere (Click to change)\n".to_string();
+    text.push_str(&format!("Cell: ({}, {})\n", sphere.cell.x, sphere.cell.y));
+    text.push_str(&format!(
+        "Material: {:?}{}\n",
+        sphere.material,
+        if has_own_material { " (edited)" } else { "" }
+    ));
+    text.push_str(&format!("Height: {:.3}\n", transform.translation.y));
+
+    let Some(material) = materials.get(handle.id()) else {
+        return;
+    };
+
+    text.push_str(&format!(
+        "{} Select field, {} Change value\n",
+        bindings.pair_label(PreviousMaterialField, NextMaterialField, &layout),
+        bindings.pair_label(MaterialValueDown, MaterialValueUp, &layout),
+    ));
+    for field in MaterialField::ALL {
+        text.push_str(&format!(
+            "{} {}: {}\n",
+            if field == field_selection.current() {
+                ">"
+            } else {
+                " "
+            },
+            field.name(),
+            field.get(material)
+        ));
+    }
+}
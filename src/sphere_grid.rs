@@ -0,0 +1,342 @@
+//! A grid of emissive and gray spheres bouncing in a wave, to give bloom something to work on.
+
+use bevy::diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic};
+use bevy::prelude::*;
+use bevy::utils::Instant;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::bindings::{TunerAction, TunerBindings};
+use crate::lights::{limit_sphere_lights, SphereLight, SphereLightSettings};
+use crate::motion::BounceMotion;
+use crate::physics::{
+    apply_gravity, collide_bodies, collide_with_ground, move_bodies, PhysicsBody, PhysicsSettings,
+};
+
+/// How long `bounce_spheres` took each frame, in milliseconds.
+pub const BOUNCE_SPHERES_TIME: DiagnosticPath = DiagnosticPath::const_new("systems/bounce_spheres");
+/// How long the sphere-sphere collisions took each fixed step, in milliseconds.
+pub const COLLIDE_BODIES_TIME: DiagnosticPath = DiagnosticPath::const_new("systems/collide_bodies");
+
+/// Spawns the sphere grid at startup and animates it.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use application::SphereGridPlugin;
+/// App::new().add_plugins(SphereGridPlugin::new().with_size(20, 20).with_spacing(1.5));
+/// ```
+#[derive(Clone)]
+pub struct SphereGridPlugin {
+    config: SphereGridConfig,
+}
+
+/// The layout of the sphere grid, available as a resource once [`SphereGridPlugin`] is added.
+#[derive(Resource, Clone, Debug)]
+pub struct SphereGridConfig {
+    /// Number of spheres along the x and z axes. The grid is centred on the origin.
+    pub size: UVec2,
+    /// Distance between the centres of neighbouring spheres.
+    pub spacing: f32,
+    pub radius: f32,
+    /// Icosphere subdivisions of the sphere mesh. Lower it for very large grids, where the
+    /// triangle count starts to dominate the frame.
+    pub subdivisions: usize,
+    pub bouncing: bool,
+    /// How bouncing spheres move. Cycling the pattern at runtime updates this too.
+    pub motion: BounceMotion,
+    /// Gives each emissive sphere a matching point light when set.
+    pub lights: Option<SphereLightSettings>,
+    /// Simulates the spheres with these settings instead of bouncing them. Takes precedence over
+    /// `bouncing`.
+    pub physics: Option<PhysicsSettings>,
+    /// Seeds the [`SceneRng`] which picks each sphere's material.
+    pub seed: u64,
+}
+
+impl Default for SphereGridConfig {
+    fn default() -> Self {
+        Self {
+            size: UVec2::new(10, 10),
+            spacing: 2.0,
+            radius: 0.5,
+            subdivisions: 5,
+            bouncing: true,
+            motion: BounceMotion::default(),
+            lights: None,
+            physics: None,
+            seed: 0,
+        }
+    }
+}
+
+impl Default for SphereGridPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SphereGridPlugin {
+    pub fn new() -> Self {
+        Self {
+            config: SphereGridConfig::default(),
+        }
+    }
+
+    pub fn with_size(mut self, x: u32, z: u32) -> Self {
+        self.config.size = UVec2::new(x, z);
+        self
+    }
+
+    pub fn with_spacing(mut self, spacing: f32) -> Self {
+        self.config.spacing = spacing;
+        self
+    }
+
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.config.radius = radius;
+        self
+    }
+
+    pub fn with_subdivisions(mut self, subdivisions: usize) -> Self {
+        self.config.subdivisions = subdivisions;
+        self
+    }
+
+    pub fn with_bouncing(mut self, bouncing: bool) -> Self {
+        self.config.bouncing = bouncing;
+        self
+    }
+
+    pub fn with_motion(mut self, motion: BounceMotion) -> Self {
+        self.config.motion = motion;
+        self
+    }
+
+    pub fn with_lights(mut self, lights: SphereLightSettings) -> Self {
+        self.config.lights = Some(lights);
+        self
+    }
+
+    pub fn with_physics(mut self, physics: PhysicsSettings) -> Self {
+        self.config.physics = Some(physics);
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.config.seed = seed;
+        self
+    }
+}
+
+impl Plugin for SphereGridPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.config.clone())
+            .insert_resource(SceneRng::new(self.config.seed))
+            .register_diagnostic(Diagnostic::new(BOUNCE_SPHERES_TIME).with_suffix("ms"))
+            .add_systems(Startup, setup_scene)
+            .add_systems(
+                Update,
+                (cycle_bounce_motion, bounce_spheres, limit_sphere_lights).chain(),
+            );
+
+        // The simulation steps at a fixed rate, so it plays out the same whatever the frame rate.
+        if self.config.physics.is_some() {
+            app.register_diagnostic(Diagnostic::new(COLLIDE_BODIES_TIME).with_suffix("ms"))
+                .add_systems(
+                    FixedUpdate,
+                    (
+                        apply_gravity,
+                        move_bodies,
+                        collide_bodies,
+                        collide_with_ground,
+                    )
+                        .chain(),
+                );
+        }
+    }
+}
+
+/// The [`ChaCha8Rng`] stream the physics start values are drawn from. The [`SceneRng`] uses stream
+/// 0.
+const PHYSICS_STREAM: u64 = 1;
+
+/// The random number generator behind the scene's random choices other than physics. ChaCha8
+/// has a fixed output for a given seed, so layouts are the same on every platform and toolchain.
+#[derive(Resource)]
+pub struct SceneRng(pub ChaCha8Rng);
+
+impl SceneRng {
+    pub fn new(seed: u64) -> Self {
+        Self(ChaCha8Rng::seed_from_u64(seed))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+fn setup_scene(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut rng: ResMut<SceneRng>,
+    config: Res<SphereGridConfig>,
+) {
+    let material_emissive1 = materials.add(StandardMaterial {
+        emissive: Color::rgb_linear(23000.0, 9000.0, 3000.0), // Put something bright in a dark environment to see the effect
+        ..default()
+    });
+    let material_emissive2 = materials.add(StandardMaterial {
+        emissive: Color::rgb_linear(3000.0, 23000.0, 9000.0),
+        ..default()
+    });
+    let material_emissive3 = materials.add(StandardMaterial {
+        emissive: Color::rgb_linear(9000.0, 3000.0, 23000.0),
+        ..default()
+    });
+    let material_non_emissive = materials.add(StandardMaterial {
+        base_color: Color::GRAY,
+        ..default()
+    });
+
+    let mesh = meshes.add(
+        Sphere::new(config.radius)
+            .mesh()
+            .ico(config.subdivisions)
+            .unwrap(),
+    );
+
+    // Physics start values come from their own stream of the same seed, so switching physics on
+    // does not change which spheres get which colors.
+    let mut physics_rng = ChaCha8Rng::seed_from_u64(config.seed);
+    physics_rng.set_stream(PHYSICS_STREAM);
+
+    let start = -(config.size.as_ivec2() / 2);
+    let end = start + config.size.as_ivec2();
+
+    for x in start.x..end.x {
+        for z in start.y..end.y {
+            // The spheres are visited in a fixed order, so the same seed always gives the same
+            // spheres the same colors.
+            let (kind, material) = match rng.0.gen_range(0..6) {
+                0 => (SphereMaterial::Emissive1, material_emissive1.clone()),
+                1 => (SphereMaterial::Emissive2, material_emissive2.clone()),
+                2 => (SphereMaterial::Emissive3, material_emissive3.clone()),
+                3..=5 => (SphereMaterial::NonEmissive, material_non_emissive.clone()),
+                _ => unreachable!(),
+            };
+
+            let mut transform =
+                Transform::from_xyz(x as f32 * config.spacing, 0.0, z as f32 * config.spacing);
+
+            // Simulated spheres are dropped from random heights, drifting sideways so they run
+            // into their neighbours.
+            let body = config.physics.map(|physics| {
+                transform.translation.y = physics.ground_height
+                    + config.radius
+                    + physics_rng.gen::<f32>() * physics.drop_height;
+
+                let angle = physics_rng.gen_range(0.0..std::f32::consts::TAU);
+                let speed = physics_rng.gen::<f32>() * physics.max_initial_speed;
+                PhysicsBody {
+                    velocity: Vec3::new(angle.cos(), 0.0, angle.sin()) * speed,
+                }
+            });
+
+            let emission = materials
+                .get(&material)
+                .map_or(Color::BLACK, |material| material.emissive);
+
+            let mut sphere = commands.spawn((
+                PbrBundle {
+                    mesh: mesh.clone(),
+                    material,
+                    transform,
+                    ..default()
+                },
+                GridSphere {
+                    cell: IVec2::new(x, z),
+                    material: kind,
+                },
+            ));
+
+            if let Some(body) = body {
+                sphere.insert(body);
+            } else if config.bouncing {
+                sphere.insert((Bouncing, config.motion));
+            }
+
+            // As a child the light follows the sphere wherever it moves. It starts off until
+            // `limit_sphere_lights` picks the ones nearest the camera.
+            if let Some(lights) = config
+                .lights
+                .filter(|_| kind != SphereMaterial::NonEmissive)
+            {
+                sphere.with_children(|parent| {
+                    parent.spawn((
+                        PointLightBundle {
+                            point_light: lights.light_for(emission),
+                            visibility: Visibility::Hidden,
+                            ..default()
+                        },
+                        SphereLight,
+                    ));
+                });
+            }
+        }
+    }
+}
+
+/// Which of the shared grid materials a sphere was spawned with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SphereMaterial {
+    Emissive1,
+    Emissive2,
+    Emissive3,
+    NonEmissive,
+}
+
+/// A sphere in the grid, and the grid cell it was spawned in.
+#[derive(Component, Debug)]
+pub struct GridSphere {
+    pub cell: IVec2,
+    pub material: SphereMaterial,
+}
+
+#[derive(Component)]
+pub struct Bouncing;
+
+fn bounce_spheres(
+    time: Res<Time>,
+    mut query: Query<(&mut Transform, &BounceMotion), With<Bouncing>>,
+    mut diagnostics: Diagnostics,
+) {
+    let start = Instant::now();
+    let t = time.elapsed_seconds();
+
+    query.par_iter_mut().for_each(|(mut transform, motion)| {
+        transform.translation.y = motion.height(transform.translation.xz(), t);
+    });
+
+    diagnostics.add_measurement(&BOUNCE_SPHERES_TIME, || {
+        start.elapsed().as_secs_f64() * 1000.0
+    });
+}
+
+/// Moves every bouncing sphere on to the next [`MotionPattern`](crate::motion::MotionPattern),
+/// when the [`BloomTunerPlugin`](crate::BloomTunerPlugin)'s bindings are available.
+fn cycle_bounce_motion(
+    keycode: Res<ButtonInput<KeyCode>>,
+    bindings: Option<Res<TunerBindings>>,
+    mut config: ResMut<SphereGridConfig>,
+    mut query: Query<&mut BounceMotion>,
+) {
+    if !bindings
+        .is_some_and(|bindings| bindings.just_pressed(&keycode, TunerAction::CycleBounceMotion))
+    {
+        return;
+    }
+
+    config.motion.pattern = config.motion.pattern.next();
+    for mut motion in query.iter_mut() {
+        motion.pattern = config.motion.pattern;
+    }
+}
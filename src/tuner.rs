@@ -0,0 +1,504 @@
+//! Keyboard driven tuning of the `BloomSettings` and FOV of a camera, with a HUD listing the
+//! current values and their keys.
+
+use bevy::core_pipeline::{
+    bloom::{BloomCompositeMode, BloomSettings},
+    tonemapping::Tonemapping,
+};
+use bevy::ecs::system::SystemParam;
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::render::camera::ScalingMode;
+use bevy::transform::TransformSystem;
+use std::path::PathBuf;
+
+use crate::bindings::{
+    learn_keyboard_layout, step_modifier, HeldActions, KeyboardLayout, TunerAction, TunerBindings,
+    DEFAULT_BINDINGS_PATH, DEFAULT_USER_BINDINGS_PATH,
+};
+use crate::comparison::{
+    handle_comparison_keys, setup_comparison_hud, sync_comparison_cameras, update_comparison_hud,
+};
+use crate::conflicts::{check_key_conflicts, KeyConflicts};
+use crate::environment::SceneEnvironment;
+use crate::flycam::sprinting;
+use crate::gamepad::GamepadMenu;
+use crate::history::{record_history, undo_redo, TunerHistory};
+use crate::parameters::TunerParameter;
+use crate::presets::{
+    apply_startup_preset, handle_preset_keys, PresetSelection, DEFAULT_PRESET_DIR,
+};
+use crate::sphere_grid::SphereGridConfig;
+use crate::user_settings::UserSettings;
+
+/// Every tonemapping operator, in the order they are cycled through.
+pub const TONEMAPPERS: [Tonemapping; 8] = [
+    Tonemapping::None,
+    Tonemapping::Reinhard,
+    Tonemapping::ReinhardLuminance,
+    Tonemapping::AcesFitted,
+    Tonemapping::AgX,
+    Tonemapping::SomewhatBoringDisplayTransform,
+    Tonemapping::TonyMcMapface,
+    Tonemapping::BlenderFilmic,
+];
+
+fn next_tonemapping(tonemapping: Tonemapping) -> Tonemapping {
+    let index = TONEMAPPERS
+        .iter()
+        .position(|&t| t == tonemapping)
+        .unwrap_or_default();
+
+    TONEMAPPERS[(index + 1) % TONEMAPPERS.len()]
+}
+
+pub fn composite_mode_name(mode: BloomCompositeMode) -> &'static str {
+    match mode {
+        BloomCompositeMode::EnergyConserving => "Energy-conserving",
+        BloomCompositeMode::Additive => "Additive",
+    }
+}
+
+/// Adds the bloom tuner HUD and key handling. The tuner drives the camera marked with
+/// [`TunerCamera`], which must have `hdr` enabled for bloom to be visible.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use application::BloomTunerPlugin;
+/// App::new().add_plugins(BloomTunerPlugin::new().with_preset_dir("my_presets"));
+/// ```
+#[derive(Clone)]
+pub struct BloomTunerPlugin {
+    config: TunerConfig,
+}
+
+/// Marks the camera whose bloom settings and projection are tuned.
+#[derive(Component)]
+pub struct TunerCamera;
+
+/// Marks the text entity the tuner writes its HUD into.
+#[derive(Component)]
+pub struct TunerHud;
+
+/// Settings shared by the tuner systems, available as a resource once [`BloomTunerPlugin`] is added.
+#[derive(Resource, Clone)]
+pub struct TunerConfig {
+    /// Where the default [`TunerBindings`] are loaded from. Never written.
+    pub bindings_path: PathBuf,
+    /// Where rebound keys are saved, and loaded from on top of the defaults.
+    pub user_bindings_path: PathBuf,
+    pub preset_dir: PathBuf,
+    /// The settings bloom is re-enabled with after being toggled off.
+    pub default_settings: BloomSettings,
+    /// A preset in `preset_dir` applied to the [`TunerCamera`] once the app has started.
+    pub startup_preset: Option<String>,
+}
+
+impl Default for BloomTunerPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BloomTunerPlugin {
+    pub fn new() -> Self {
+        Self {
+            config: TunerConfig {
+                bindings_path: DEFAULT_BINDINGS_PATH.into(),
+                user_bindings_path: DEFAULT_USER_BINDINGS_PATH.into(),
+                preset_dir: DEFAULT_PRESET_DIR.into(),
+                default_settings: BloomSettings::NATURAL,
+                startup_preset: None,
+            },
+        }
+    }
+
+    pub fn with_bindings_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.bindings_path = path.into();
+        self
+    }
+
+    pub fn with_user_bindings_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.user_bindings_path = path.into();
+        self
+    }
+
+    pub fn with_preset_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.config.preset_dir = dir.into();
+        self
+    }
+
+    pub fn with_default_settings(mut self, settings: BloomSettings) -> Self {
+        self.config.default_settings = settings;
+        self
+    }
+
+    /// Loads the named preset onto the [`TunerCamera`] at startup. A trailing `.ron` is ignored.
+    pub fn with_startup_preset(mut self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        self.config.startup_preset = Some(
+            name.strip_suffix(".ron")
+                .map_or(name.clone(), str::to_string),
+        );
+        self
+    }
+}
+
+impl Plugin for BloomTunerPlugin {
+    fn build(&self, app: &mut App) {
+        let mut selection = PresetSelection::new(&self.config.preset_dir);
+        if let Some(name) = &self.config.startup_preset {
+            selection.select(name);
+        }
+
+        app.insert_resource(TunerBindings::load_with_overrides(
+            &self.config.bindings_path,
+            &self.config.user_bindings_path,
+        ))
+        .insert_resource(selection)
+        .insert_resource(self.config.clone())
+        .init_resource::<TunerHistory>()
+        .init_resource::<KeyConflicts>()
+        .init_resource::<KeyboardLayout>()
+        .add_systems(Startup, (setup_hud, setup_comparison_hud))
+        .add_systems(PostStartup, apply_startup_preset)
+        .add_systems(PreUpdate, learn_keyboard_layout.after(InputSystem))
+        .add_systems(
+            Update,
+            (
+                check_key_conflicts,
+                handle_comparison_keys,
+                handle_preset_keys,
+                undo_redo,
+                update_bloom_settings,
+                record_history,
+                update_comparison_hud,
+            )
+                .chain(),
+        )
+        .add_systems(
+            PostUpdate,
+            sync_comparison_cameras.before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+fn setup_hud(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 20.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(12.0),
+            left: Val::Px(12.0),
+            ..default()
+        }),
+        TunerHud,
+    ));
+}
+
+/// The settings of whichever projection is not in use, so toggling back restores them.
+struct InactiveProjections {
+    perspective: PerspectiveProjection,
+    orthographic: OrthographicProjection,
+}
+
+impl Default for InactiveProjections {
+    fn default() -> Self {
+        Self {
+            perspective: default(),
+            orthographic: OrthographicProjection {
+                scale: 10.0,
+                scaling_mode: ScalingMode::FixedVertical(2.0),
+                ..default()
+            },
+        }
+    }
+}
+
+fn toggle_projection(projection: &mut Projection, inactive: &mut InactiveProjections) {
+    *projection = match projection {
+        Projection::Perspective(persp) => {
+            inactive.perspective = persp.clone();
+            Projection::Orthographic(inactive.orthographic.clone())
+        }
+        Projection::Orthographic(ortho) => {
+            inactive.orthographic = ortho.clone();
+            Projection::Perspective(inactive.perspective.clone())
+        }
+    };
+}
+
+/// Everything listed in the HUD besides the camera's own settings: key binding conflicts, and the
+/// state of the other plugins when they are added.
+#[derive(SystemParam)]
+struct SceneStatus<'w> {
+    keycode: Res<'w, ButtonInput<KeyCode>>,
+    conflicts: Res<'w, KeyConflicts>,
+    grid_config: Option<Res<'w, SphereGridConfig>>,
+    environment: Option<Res<'w, SceneEnvironment>>,
+    user_settings: Option<Res<'w, UserSettings>>,
+    gamepads: Res<'w, Gamepads>,
+    gamepad_menu: Option<Res<'w, GamepadMenu>>,
+}
+
+impl SceneStatus<'_> {
+    fn hud_lines(
+        &self,
+        bindings: &TunerBindings,
+        layout: &KeyboardLayout,
+        projection: &Projection,
+    ) -> String {
+        use TunerAction::*;
+
+        let mut lines = String::new();
+
+        if let Some(grid_config) = &self.grid_config {
+            lines.push_str(&match grid_config.physics {
+                Some(_) => "Motion: physics\n".to_string(),
+                None => format!(
+                    "({}) Motion: {}\n",
+                    bindings.label(CycleBounceMotion, layout),
+                    grid_config.motion.pattern
+                ),
+            });
+        }
+
+        if let Some(environment) = &self.environment {
+            let on_off = |on| if on { "On" } else { "Off" };
+            lines.push_str(&format!(
+                "({}) Ground: {}, ({}) Skybox: {}, {} Ambient: {}\n",
+                bindings.label(ToggleGround, layout),
+                on_off(environment.ground),
+                bindings.label(ToggleSkybox, layout),
+                on_off(environment.skybox),
+                bindings.pair_label(AmbientUp, AmbientDown, layout),
+                environment.ambient_brightness
+            ));
+        }
+
+        if let Some(user_settings) = &self.user_settings {
+            let flycam = user_settings.flycam;
+            lines.push_str(&format!(
+                "{} Fly speed: {:.1}{} (wheel, hold Shift: x{} sprint)\n",
+                bindings.pair_label(FlySpeedUp, FlySpeedDown, layout),
+                flycam.speed,
+                if sprinting(&self.keycode) {
+                    " sprinting"
+                } else {
+                    ""
+                },
+                flycam.sprint_multiplier
+            ));
+            lines.push_str(&format!(
+                "{} Sensitivity: {:.6} (Alt + wheel), ({}) Save settings\n",
+                bindings.pair_label(SensitivityUp, SensitivityDown, layout),
+                flycam.sensitivity,
+                bindings.label(SaveUserSettings, layout)
+            ));
+        }
+
+        if let Some(gamepad_menu) = &self.gamepad_menu {
+            if self.gamepads.iter().next().is_some() {
+                lines.push_str(&format!(
+                    "(D-pad) Gamepad adjusts: {}\n",
+                    gamepad_menu.current().name(projection)
+                ));
+            }
+        }
+
+        for conflict in &self.conflicts.0 {
+            lines.push_str(&format!("Key conflict: {}\n", conflict.label(layout)));
+        }
+
+        lines
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_bloom_settings(
+    mut camera: Query<(Entity, Option<&mut BloomSettings>, &mut Tonemapping), With<TunerCamera>>,
+    mut text: Query<&mut Text, With<TunerHud>>,
+    mut commands: Commands,
+    keycode: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    mut held: Local<HeldActions>,
+    mut inactive_projections: Local<InactiveProjections>,
+    mut proj_query: Query<&mut Projection, With<TunerCamera>>,
+    bindings: Res<TunerBindings>,
+    preset_selection: Res<PresetSelection>,
+    history: Res<TunerHistory>,
+    config: Res<TunerConfig>,
+    layout: Res<KeyboardLayout>,
+    scene: SceneStatus,
+) {
+    use TunerAction::*;
+
+    held.update(&bindings, &keycode, time.delta_seconds());
+
+    let (Ok((entity, mut bloom_settings, mut tonemapping)), Ok(mut text), Ok(projection)) = (
+        camera.get_single_mut(),
+        text.get_single_mut(),
+        proj_query.get_single_mut(),
+    ) else {
+        return;
+    };
+    let text = &mut text.sections[0].value;
+
+    let projection = projection.into_inner();
+
+    if bindings.just_pressed(&keycode, ToggleProjection) {
+        toggle_projection(projection, &mut inactive_projections);
+    }
+
+    if bindings.just_pressed(&keycode, CycleTonemapping) {
+        *tonemapping = next_tonemapping(*tonemapping);
+    }
+
+    // The FOV can be adjusted while bloom is off, the bloom parameters only while it is on.
+    let dt = time.delta_seconds() * step_modifier(&keycode);
+    for parameter in TunerParameter::ALL {
+        let (up, down) = parameter.actions();
+        let change = held.axis(up, down) * parameter.adjust_rate(projection) * dt;
+        if change != 0.0 {
+            parameter.adjust(bloom_settings.as_deref_mut(), projection, change);
+        }
+    }
+
+    let fov_line = match projection {
+        Projection::Perspective(persp) => format!(
+            "{} FOV: {}\n",
+            bindings.pair_label(FovUp, FovDown, &layout),
+            persp.fov.to_degrees()
+        ),
+        Projection::Orthographic(ortho) => format!(
+            "{} Ortho scale: {}\n",
+            bindings.pair_label(FovUp, FovDown, &layout),
+            ortho.scale
+        ),
+    };
+    let projection_line = format!(
+        "({}) Projection: {}\n",
+        bindings.label(ToggleProjection, &layout),
+        match projection {
+            Projection::Perspective(_) => "Perspective",
+            Projection::Orthographic(_) => "Orthographic",
+        }
+    );
+    let tonemapping_line = format!(
+        "({}) Tonemapping: {:?}\n",
+        bindings.label(CycleTonemapping, &layout),
+        *tonemapping
+    );
+    let history_line = format!(
+        "({}/{}) History: {}/{}\n",
+        bindings.shortcut_label(Undo, &layout),
+        bindings.shortcut_label(Redo, &layout),
+        history.position(),
+        history.len()
+    );
+    let scene_lines = scene.hud_lines(&bindings, &layout, projection);
+    let preset_line = format!(
+        "({}/{}/{}/{}) Preset (save/save new/next/load): {}\n",
+        bindings.label(SavePreset, &layout),
+        bindings.label(SaveNewPreset, &layout),
+        bindings.label(NextPreset, &layout),
+        bindings.label(LoadPreset, &layout),
+        preset_selection.current()
+    );
+
+    match bloom_settings {
+        Some(mut bloom_settings) => {
+            *text = format!(
+                "BloomSettings (Toggle: {}, hold Shift: coarse, Ctrl: fine)\n",
+                bindings.label(ToggleBloom, &layout)
+            );
+            text.push_str(&format!(
+                "{} Intensity: {}\n",
+                bindings.pair_label(IntensityUp, IntensityDown, &layout),
+                bloom_settings.intensity
+            ));
+            text.push_str(&format!(
+                "{} Low-frequency boost: {}\n",
+                bindings.pair_label(LowFrequencyBoostUp, LowFrequencyBoostDown, &layout),
+                bloom_settings.low_frequency_boost
+            ));
+            text.push_str(&format!(
+                "{} Low-frequency boost curvature: {}\n",
+                bindings.pair_label(
+                    LowFrequencyBoostCurvatureUp,
+                    LowFrequencyBoostCurvatureDown,
+                    &layout
+                ),
+                bloom_settings.low_frequency_boost_curvature
+            ));
+            text.push_str(&format!(
+                "{} High-pass frequency: {}\n",
+                bindings.pair_label(HighPassFrequencyUp, HighPassFrequencyDown, &layout),
+                bloom_settings.high_pass_frequency
+            ));
+            text.push_str(&format!(
+                "{} Mode: {}\n",
+                bindings.pair_label(
+                    CompositeModeAdditive,
+                    CompositeModeEnergyConserving,
+                    &layout
+                ),
+                composite_mode_name(bloom_settings.composite_mode)
+            ));
+            text.push_str(&format!(
+                "{} Threshold: {}\n",
+                bindings.pair_label(ThresholdUp, ThresholdDown, &layout),
+                bloom_settings.prefilter_settings.threshold
+            ));
+            text.push_str(&format!(
+                "{} Threshold softness: {}\n",
+                bindings.pair_label(ThresholdSoftnessUp, ThresholdSoftnessDown, &layout),
+                bloom_settings.prefilter_settings.threshold_softness
+            ));
+            text.push_str(&fov_line);
+            text.push_str(&projection_line);
+            text.push_str(&tonemapping_line);
+            text.push_str(&scene_lines);
+            text.push_str(&preset_line);
+            text.push_str(&history_line);
+
+            if bindings.just_pressed(&keycode, CompositeModeAdditive) {
+                bloom_settings.composite_mode = BloomCompositeMode::Additive;
+            } else if bindings.just_pressed(&keycode, CompositeModeEnergyConserving) {
+                bloom_settings.composite_mode = BloomCompositeMode::EnergyConserving;
+            }
+
+            if bindings.just_pressed(&keycode, ToggleBloom) {
+                commands.entity(entity).remove::<BloomSettings>();
+            }
+        }
+
+        None => {
+            *text = format!(
+                "Bloom: Off (Toggle: {})\n",
+                bindings.label(ToggleBloom, &layout)
+            );
+            text.push_str(&fov_line);
+            text.push_str(&projection_line);
+            text.push_str(&tonemapping_line);
+            text.push_str(&scene_lines);
+            text.push_str(&preset_line);
+            text.push_str(&history_line);
+
+            if bindings.just_pressed(&keycode, ToggleBloom) {
+                commands
+                    .entity(entity)
+                    .insert(config.default_settings.clone());
+            }
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Settings the user changes at runtime and keeps between runs, stored as RON in the assets folder.
+//! The defaults are tracked in the repository and never written; the user's own settings are
+//! saved to a separate file, ignored by git, which is loaded instead when present.
+
+use bevy::prelude::*;
+use bevy_flycam::prelude::KeyBindings;
+use serde::{Deserialize, Serialize};
+use std::{fmt, fs, io, path::Path};
+
+pub const DEFAULT_SETTINGS_PATH: &str = "assets/config/settings.ron";
+pub const DEFAULT_USER_SETTINGS_PATH: &str = "assets/config/user/settings.ron";
+
+/// Everything saved in the user settings file. Settings missing from the file keep their
+/// defaults.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct UserSettings {
+    pub flycam: FlycamSettings,
+    pub flycam_keys: FlycamKeys,
+}
+
+/// How the flycam moves and turns.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct FlycamSettings {
+    pub speed: f32,
+    pub sensitivity: f32,
+    /// What the speed is multiplied by while Shift is held.
+    pub sprint_multiplier: f32,
+}
+
+impl Default for FlycamSettings {
+    fn default() -> Self {
+        Self {
+            speed: 12.0,
+            sensitivity: 0.00015,
+            sprint_multiplier: 3.0,
+        }
+    }
+}
+
+/// The keys moving the flycam, in a form which can be saved. bevy_flycam's own [`KeyBindings`]
+/// cannot be serialized.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct FlycamKeys {
+    pub move_forward: KeyCode,
+    pub move_backward: KeyCode,
+    pub move_left: KeyCode,
+    pub move_right: KeyCode,
+    pub move_ascend: KeyCode,
+    pub move_descend: KeyCode,
+    pub toggle_grab_cursor: KeyCode,
+}
+
+impl Default for FlycamKeys {
+    /// bevy_flycam's defaults, except for ascending and descending, which are moved off Space and
+    /// Shift so they do not collide with the tuner.
+    fn default() -> Self {
+        Self::from(&KeyBindings {
+            move_ascend: KeyCode::KeyE,
+            move_descend: KeyCode::KeyQ,
+            ..default()
+        })
+    }
+}
+
+impl From<&KeyBindings> for FlycamKeys {
+    fn from(bindings: &KeyBindings) -> Self {
+        Self {
+            move_forward: bindings.move_forward,
+            move_backward: bindings.move_backward,
+            move_left: bindings.move_left,
+            move_right: bindings.move_right,
+            move_ascend: bindings.move_ascend,
+            move_descend: bindings.move_descend,
+            toggle_grab_cursor: bindings.toggle_grab_cursor,
+        }
+    }
+}
+
+impl From<FlycamKeys> for KeyBindings {
+    fn from(keys: FlycamKeys) -> Self {
+        Self {
+            move_forward: keys.move_forward,
+            move_backward: keys.move_backward,
+            move_left: keys.move_left,
+            move_right: keys.move_right,
+            move_ascend: keys.move_ascend,
+            move_descend: keys.move_descend,
+            toggle_grab_cursor: keys.toggle_grab_cursor,
+        }
+    }
+}
+
+impl UserSettings {
+    /// Loads the settings from `path`, falling back to the defaults if it cannot be read.
+    pub fn load_or_default(path: &Path) -> Self {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) => {
+                info!(
+                    "Using default user settings, could not read {}: {err}",
+                    path.display()
+                );
+                return Self::default();
+            }
+        };
+
+        ron::from_str(&contents).unwrap_or_else(|err| {
+            error!(
+                "Using default user settings, could not parse {}: {err}",
+                path.display()
+            );
+            Self::default()
+        })
+    }
+
+    /// Loads the user's own settings from `path` if they have saved any, otherwise the defaults
+    /// from `defaults_path`.
+    pub fn load_user_or_defaults(path: &Path, defaults_path: &Path) -> Self {
+        if path.exists() {
+            Self::load_or_default(path)
+        } else {
+            Self::load_or_default(defaults_path)
+        }
+    }
+
+    /// These settings, with every value which differs between `before` and `after` taken from
+    /// `after`.
+    pub fn with_changes(mut self, before: &Self, after: &Self) -> Self {
+        fn take<T: PartialEq + Copy>(value: &mut T, before: T, after: T) {
+            if before != after {
+                *value = after;
+            }
+        }
+
+        let flycam = &mut self.flycam;
+        take(&mut flycam.speed, before.flycam.speed, after.flycam.speed);
+        take(
+            &mut flycam.sensitivity,
+            before.flycam.sensitivity,
+            after.flycam.sensitivity,
+        );
+        take(
+            &mut flycam.sprint_multiplier,
+            before.flycam.sprint_multiplier,
+            after.flycam.sprint_multiplier,
+        );
+        take(&mut self.flycam_keys, before.flycam_keys, after.flycam_keys);
+
+        self
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), SettingsError> {
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, contents)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum SettingsError {
+    Io(io::Error),
+    Serialize(ron::Error),
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Serialize(err) => write!(f, "could not serialize settings: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for SettingsError {}
+
+impl From<io::Error> for SettingsError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ron::Error> for SettingsError {
+    fn from(err: ron::Error) -> Self {
+        Self::Serialize(err)
+    }
+}